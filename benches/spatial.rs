@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use evolution_simulated::grid::SpatialGrid;
+use evolution_simulated::utils::{Bounds, Point};
+
+const RADIUS: f64 = 25.0;
+
+/// Spreads `count` points at the same density the web page uses.
+fn positions(count: usize) -> (Bounds, Vec<Point>) {
+    let side = (count as f64 * 800.0).sqrt();
+    let bounds = Bounds {
+        width: side,
+        height: side,
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let points = (0..count)
+        .map(|_| Point::new_random(&mut rng, bounds))
+        .collect();
+    (bounds, points)
+}
+
+fn nearest_linear(bounds: Bounds, points: &[Point]) -> usize {
+    let mut found = 0;
+    for (i, point) in points.iter().enumerate() {
+        let distance = |j: usize| point.torus_distance(&points[j], bounds);
+        let nearest = (0..points.len())
+            .filter(|j| *j != i && distance(*j) <= RADIUS)
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)));
+        found += nearest.is_some() as usize;
+    }
+    found
+}
+
+fn nearest_grid(grid: &mut SpatialGrid, bounds: Bounds, points: &[Point]) -> usize {
+    grid.rebuild(bounds, RADIUS, points.iter().copied());
+    let mut found = 0;
+    for (i, point) in points.iter().enumerate() {
+        let distance = |j: usize| point.torus_distance(&points[j], bounds);
+        let nearest = grid
+            .query(*point, RADIUS)
+            .filter(|j| *j != i && distance(*j) <= RADIUS)
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)));
+        found += nearest.is_some() as usize;
+    }
+    found
+}
+
+fn mate_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mate_search");
+    for count in [100, 1_000, 10_000] {
+        let (bounds, points) = positions(count);
+        group.bench_with_input(BenchmarkId::new("linear", count), &points, |b, points| {
+            b.iter(|| nearest_linear(bounds, black_box(points)))
+        });
+        let mut grid = SpatialGrid::default();
+        group.bench_with_input(BenchmarkId::new("grid", count), &points, |b, points| {
+            b.iter(|| nearest_grid(&mut grid, bounds, black_box(points)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, mate_search);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+use evolution_simulated::creature::Creature;
+use evolution_simulated::world::World;
+
+const POPULATIONS: [usize; 3] = [100, 1_000, 10_000];
+
+/// A world of `count` creatures old enough to breed, spread at the same
+/// density the web page uses.
+fn populated_world(count: usize) -> World {
+    let side = (count as f64 * 800.0).sqrt();
+    let mut world = World::with_seed(0);
+    world.config.width = side;
+    world.config.height = side;
+    world.config.max_creatures = count;
+    world.config.starting_pop = count;
+    world.spawn_random_organisms(count);
+    for _ in 0..world.config.breeding_age {
+        world.tick();
+    }
+    world.config.max_creatures = count * 2;
+    world
+}
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for count in POPULATIONS {
+        let world = populated_world(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &world, |b, world| {
+            b.iter_batched(|| world.clone(), |mut world| world.tick(), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+fn reproduce(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reproduce");
+    for count in POPULATIONS {
+        let world = populated_world(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &world, |b, world| {
+            b.iter_batched(
+                || world.clone(),
+                |mut world| world.reproduce(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn does_overlap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("does_overlap");
+    for count in POPULATIONS {
+        let world = populated_world(count);
+        let bounds = world.config.bounds();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &world.creatures,
+            |b, creatures| {
+                b.iter(|| {
+                    creatures
+                        .windows(2)
+                        .filter(|pair| Creature::does_overlap(&pair[0], &pair[1], bounds))
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn info_as_json(c: &mut Criterion) {
+    let mut group = c.benchmark_group("info_as_json");
+    for count in POPULATIONS {
+        let world = populated_world(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &world, |b, world| {
+            b.iter(|| black_box(world).info_as_json())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick, reproduce, does_overlap, info_as_json);
+criterion_main!(benches);
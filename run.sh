@@ -1,4 +1,4 @@
-wasm-pack build --target web
+wasm-pack build --target web -- --features web
 rmdir -f web/pkg
 cp -r pkg web
 cd web
@@ -0,0 +1,327 @@
+use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    MouseButton, MouseEventKind,
+};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use evolution_simulated::config::Config;
+use evolution_simulated::render::braille::BrailleRenderer;
+use evolution_simulated::render::parse_colour;
+use evolution_simulated::world::World;
+
+const USAGE: &str = "Usage: evolution-tui [options]
+
+Options:
+    --seed <n>            Seed for the random number generator of a new world, not with --load
+    --config <path>       Load settings from a .toml or .json file
+    --load <path>         Resume from a world snapshot instead of spawning a new world
+    -h, --help            Print this message
+
+Keys:
+    space                 Pause or resume
+    n                     Step one tick
+    + / -                 Run faster or slower
+    tab / shift+tab       Select the next or previous creature, or click on one
+    esc                   Clear the selection
+    q                     Quit";
+
+const PANEL_WIDTH: u16 = 30;
+const FRAME_TIME: Duration = Duration::from_millis(50);
+const MAX_TICKS_PER_FRAME: u32 = 64;
+
+/// Matches the radius the web page uses to pick the creature under a click.
+const PICK_RADIUS: f64 = 10.0;
+
+struct Args {
+    seed: Option<u64>,
+    config_path: Option<PathBuf>,
+    load: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        seed: None,
+        config_path: None,
+        load: None,
+    };
+    let mut argv = env::args().skip(1);
+    while let Some(flag) = argv.next() {
+        let mut value = || argv.next().ok_or(format!("missing value for {}", flag));
+        match flag.as_str() {
+            "--seed" => {
+                let seed = value()?;
+                args.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("invalid value for {}: {}", flag, seed))?,
+                );
+            }
+            "--config" => args.config_path = Some(value()?.into()),
+            "--load" => args.load = Some(value()?.into()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown argument: {}", flag)),
+        }
+    }
+    if args.load.is_some() && args.seed.is_some() {
+        return Err(
+            "--seed can't be used with --load, which carries on with the snapshot's own seed"
+                .to_string(),
+        );
+    }
+    Ok(args)
+}
+
+fn build_world(args: &Args) -> Result<World, String> {
+    if let Some(path) = &args.load {
+        return World::load_from_file(path).map_err(|err| format!("{}: {}", path.display(), err));
+    }
+    let mut world = match args.seed {
+        Some(seed) => World::with_seed(seed),
+        None => World::new(),
+    };
+    if let Some(path) = &args.config_path {
+        world.config = Config::load(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    world.spawn_random_organisms(world.config.starting_pop);
+    Ok(world)
+}
+
+/// Puts the terminal into raw mode on the alternate screen, and restores it
+/// when dropped, including when drawing panics.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Viewer {
+    world: World,
+    paused: bool,
+    ticks_per_frame: u32,
+    /// The last frame drawn, used to find what a mouse click landed on.
+    map: Option<BrailleRenderer>,
+}
+
+impl Viewer {
+    fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            if self.world.creatures.is_empty() {
+                self.paused = true;
+                return;
+            }
+            self.world.tick();
+        }
+    }
+
+    /// Moves the selection `offset` places along the creature list.
+    fn cycle_selection(&mut self, offset: isize) {
+        let creatures = &self.world.creatures;
+        if creatures.is_empty() {
+            return;
+        }
+        let current = self
+            .world
+            .selected
+            .and_then(|id| creatures.iter().position(|creature| creature.id == id));
+        let next = match current {
+            Some(index) => (index as isize + offset).rem_euclid(creatures.len() as isize) as usize,
+            None if offset < 0 => creatures.len() - 1,
+            None => 0,
+        };
+        self.world.selected = Some(creatures[next].id);
+    }
+
+    fn click(&mut self, column: u16, row: u16) {
+        let Some(map) = &self.map else { return };
+        if column as usize >= map.columns() || row as usize >= map.rows() {
+            return;
+        }
+        let point = map.world_position(column as usize, row as usize);
+        self.world.selected = self
+            .world
+            .creature_near(point, PICK_RADIUS)
+            .map(|creature| creature.id);
+    }
+
+    /// Handles one input event, returning false once the user asks to quit.
+    fn handle(&mut self, event: Event, out: &mut impl Write) -> io::Result<bool> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') => return Ok(false),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(false)
+                }
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') | KeyCode::Char('.') => self.step(1),
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.ticks_per_frame = (self.ticks_per_frame * 2).min(MAX_TICKS_PER_FRAME)
+                }
+                KeyCode::Char('-') => self.ticks_per_frame = (self.ticks_per_frame / 2).max(1),
+                KeyCode::Tab => self.cycle_selection(1),
+                KeyCode::BackTab => self.cycle_selection(-1),
+                KeyCode::Esc => self.world.selected = None,
+                _ => {}
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                self.click(mouse.column, mouse.row)
+            }
+            Event::Resize(_, _) => queue!(out, Clear(ClearType::All))?,
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let mut map = BrailleRenderer::new(
+            self.world.config.bounds(),
+            columns.saturating_sub(PANEL_WIDTH).max(1) as usize,
+            rows.max(1) as usize,
+        );
+        self.world.draw(&mut map);
+
+        for row in 0..map.rows() {
+            queue!(out, MoveTo(0, row as u16))?;
+            for cell in map.row(row) {
+                match cell.colour {
+                    Some(colour) => queue!(out, SetForegroundColor(rgb(colour)))?,
+                    None => queue!(out, SetForegroundColor(Color::Reset))?,
+                }
+                match cell.highlight {
+                    Some(colour) => queue!(out, SetBackgroundColor(rgb(colour)))?,
+                    None => queue!(out, SetBackgroundColor(Color::Reset))?,
+                }
+                queue!(out, Print(cell.char()))?;
+            }
+            queue!(out, ResetColor)?;
+        }
+
+        let panel_column = map.columns() as u16 + 1;
+        for (row, (text, colour)) in self.panel().into_iter().take(rows as usize).enumerate() {
+            queue!(out, MoveTo(panel_column, row as u16))?;
+            if let Some(colour) = colour {
+                queue!(out, SetForegroundColor(rgb(colour)), Print('■'), ResetColor, Print(' '))?;
+            }
+            let width = PANEL_WIDTH as usize - if colour.is_some() { 3 } else { 1 };
+            queue!(out, Print(format!("{:<width$.width$}", text, width = width)))?;
+        }
+        self.map = Some(map);
+        out.flush()
+    }
+
+    /// The lines of the side panel, with an optional colour swatch for each.
+    fn panel(&self) -> Vec<(String, Option<[u8; 3]>)> {
+        let mut lines = vec![
+            (format!("Population  {}", self.world.creatures.len()), None),
+            (format!("Tick        {}", self.world.ticks()), None),
+            (
+                if self.paused {
+                    "Paused".to_string()
+                } else {
+                    format!("Running     {} ticks/frame", self.ticks_per_frame)
+                },
+                None,
+            ),
+            (String::new(), None),
+        ];
+        for (skin, count) in self.world.colour_counts() {
+            lines.push((format!("{:<14}{}", skin, count), Some(parse_colour(&skin.hex()))));
+        }
+        lines.push((String::new(), None));
+        let selected = self.world.selected.and_then(|id| {
+            self.world
+                .creatures
+                .iter()
+                .find(|creature| creature.id == id)
+        });
+        match selected {
+            Some(creature) => {
+                lines.push((format!("Creature {}", creature.id), None));
+                let info = json::parse(&creature.get_info_as_json(&self.world.config)).unwrap();
+                for (key, value) in info.entries() {
+                    lines.push((format!("  {:<16}{}", key, value), None));
+                }
+            }
+            None => lines.push(("Click or tab to select".to_string(), None)),
+        }
+        lines.push((String::new(), None));
+        for help in ["space pause   n step", "+/- speed     tab select", "esc deselect  q quit"] {
+            lines.push((help.to_string(), None));
+        }
+        lines
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+fn run(world: World) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut out = io::stdout();
+    let mut viewer = Viewer {
+        world,
+        paused: false,
+        ticks_per_frame: 1,
+        map: None,
+    };
+    queue!(out, Clear(ClearType::All))?;
+    loop {
+        let frame_start = Instant::now();
+        if !viewer.paused {
+            viewer.step(viewer.ticks_per_frame);
+        }
+        viewer.draw(&mut out)?;
+        while let Some(remaining) = FRAME_TIME.checked_sub(frame_start.elapsed()) {
+            if !event::poll(remaining)? {
+                break;
+            }
+            if !viewer.handle(event::read()?, &mut out)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    let world = match build_world(&args) {
+        Ok(world) => world,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    };
+    if let Err(err) = run(world) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -1,6 +1,125 @@
-pub static mut CONFIG: Config = Config::new();
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+use crate::gene::{ColourGene, Dominance};
+use crate::utils::Bounds;
+
+/// Inclusive range of accepted values for each numeric setting.
+pub const RANGES: [(&str, f64, f64); 23] = [
+    ("width", 1.0, 100_000.0),
+    ("height", 1.0, 100_000.0),
+    ("resolution", 0.1, 10.0),
+    ("starting_pop", 0.0, 100_000.0),
+    ("max_creatures", 1.0, 100_000.0),
+    ("lifespan", 1.0, 100_000.0),
+    ("reproduction_cooldown", 0.0, 100_000.0),
+    ("mate_cooldown", 0.0, 100_000.0),
+    ("breeding_age", 0.0, 100_000.0),
+    ("mate_search_radius", 0.0, 100_000.0),
+    ("birth_rate_divisor", 1.0, 100_000.0),
+    ("corpse_persistence", 0.0, 100_000.0),
+    ("pedigree_capacity", 0.0, 10_000_000.0),
+    ("colour_mutation_rate", 0.0, 1.0),
+    ("speed_mutation_rate", 0.0, 1.0),
+    ("dominance_mutation_rate", 0.0, 1.0),
+    ("polygene_mutation_rate", 0.0, 1.0),
+    // Beyond 0.25 a creature with no increasing alleles would have a negative
+    // trait value.
+    ("additive_effect", 0.0, 0.25),
+    ("environmental_noise", 0.0, 1.0),
+    ("vision_range", 0.0, 100_000.0),
+    ("slow_speed", 0.0, 100.0),
+    ("medium_speed", 0.0, 100.0),
+    ("fast_speed", 0.0, 100.0),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    Parse {
+        key: String,
+        value: String,
+    },
+    OutOfRange {
+        key: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    StartingPopExceedsMax {
+        starting_pop: usize,
+        max_creatures: usize,
+    },
+    /// `colour_order` lists a colour more than once, and so leaves another out.
+    RepeatedColour(ColourGene),
+    /// A JSON or TOML document could not be read as a config.
+    Format(String),
+    Io(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "unknown config key \"{}\"", key),
+            ConfigError::Parse { key, value } => {
+                write!(f, "invalid value \"{}\" for {}", value, key)
+            }
+            ConfigError::OutOfRange {
+                key,
+                value,
+                min,
+                max,
+            } => write!(f, "{} must be between {} and {}, got {}", key, min, max, value),
+            ConfigError::StartingPopExceedsMax {
+                starting_pop,
+                max_creatures,
+            } => write!(
+                f,
+                "starting_pop ({}) must not exceed max_creatures ({})",
+                starting_pop, max_creatures
+            ),
+            ConfigError::RepeatedColour(colour) => {
+                write!(f, "colour_order lists {} more than once", colour)
+            }
+            ConfigError::Format(err) => write!(f, "invalid config file: {}", err),
+            ConfigError::Io(err) => write!(f, "couldn't access config file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn is_toml(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::Parse {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Reads a comma separated list of every colour, e.g.
+/// `red,orange,light_blue,dark_blue,black,yellow,purple`.
+fn parse_order(key: &str, value: &str) -> Result<[ColourGene; 7], ConfigError> {
+    let error = || ConfigError::Parse {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let colours = value
+        .split(',')
+        .map(|colour| colour.parse().map_err(|_| error()))
+        .collect::<Result<Vec<ColourGene>, _>>()?;
+    colours.try_into().map_err(|_| error())
+}
+
+/// Missing fields take their default values, so a preset only needs to list
+/// the settings it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     pub width: f64,
     pub height: f64,
@@ -8,6 +127,55 @@ pub struct Config {
     pub starting_pop: usize,
     pub resolution: f64,
     pub lifespan: u32,
+    /// Ticks a creature must wait after breeding before it seeks a mate.
+    pub reproduction_cooldown: u32,
+    /// Ticks a creature must wait after breeding before it can be chosen as a mate.
+    pub mate_cooldown: u32,
+    pub breeding_age: u32,
+    pub mate_search_radius: f64,
+    /// At most `population / birth_rate_divisor` creatures are born per tick.
+    pub birth_rate_divisor: usize,
+    /// Ticks a dead creature stays in the world before it is removed.
+    pub corpse_persistence: u32,
+    /// How many records `World::pedigree` keeps before dropping those of the
+    /// creatures that died longest ago. Living creatures are always kept, so
+    /// it can briefly hold up to this plus the population.
+    pub pedigree_capacity: usize,
+    /// Chance that an inherited colour allele is replaced by another colour.
+    pub colour_mutation_rate: f64,
+    /// Chance that an inherited speed allele moves to another of slow, medium
+    /// and fast.
+    pub speed_mutation_rate: f64,
+    /// Chance that an inherited slow or medium allele has its dominance
+    /// flipped.
+    pub dominance_mutation_rate: f64,
+    /// Chance that each locus behind a quantitative trait flips as it is
+    /// inherited.
+    pub polygene_mutation_rate: f64,
+    /// How far each increasing allele behind a quantitative trait moves it,
+    /// as a fraction of the trait's mean. At the default of 0 every creature
+    /// has the mean of every trait.
+    pub additive_effect: f64,
+    /// The standard deviation of the noise added to quantitative traits at
+    /// birth, as a fraction of the trait's mean.
+    pub environmental_noise: f64,
+    /// The mean distance at which creatures notice the point they swim
+    /// towards. The default sees across the largest world.
+    pub vision_range: f64,
+    /// How heterozygous colour alleles are expressed.
+    pub colour_dominance: Dominance,
+    /// Every colour, each dominant over those after it under complete
+    /// dominance.
+    pub colour_order: [ColourGene; 7],
+    pub slow_speed: f64,
+    pub medium_speed: f64,
+    pub fast_speed: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
 }
 
 impl Config {
@@ -20,47 +188,183 @@ impl Config {
             starting_pop: 20,
             max_creatures: 100,
             lifespan: 150,
+            // Breeding Options
+            reproduction_cooldown: 30,
+            mate_cooldown: 15,
+            breeding_age: 50,
+            mate_search_radius: 25.0,
+            birth_rate_divisor: 20,
+            corpse_persistence: 15,
+            pedigree_capacity: 10_000,
+            colour_mutation_rate: 0.001,
+            speed_mutation_rate: 0.001,
+            dominance_mutation_rate: 0.001,
+            polygene_mutation_rate: 0.001,
+            additive_effect: 0.0,
+            environmental_noise: 0.0,
+            vision_range: 100_000.0,
+            colour_dominance: Dominance::Complete,
+            colour_order: ColourGene::ALL,
+            // Movement Options
+            slow_speed: 1.5,
+            medium_speed: 2.0,
+            fast_speed: 2.5,
         }
     }
 
     pub fn as_json(&self) -> String {
-        String::from(
-            r#"{
-            "width": "#,
-        ) + &self.width.to_string()
-            + r#",
-            "height": "#
-            + &self.height.to_string()
-            + r#",
-            "resolution": "#
-            + &self.resolution.to_string()
-            + r#",
-            "starting_pop": "#
-            + &self.starting_pop.to_string()
-            + r#",
-            "max_creatures": "#
-            + &self.max_creatures.to_string()
-            + r#",
-            "lifespan": "#
-            + &self.lifespan.to_string()
-            + r#"
-        }"#
+        serde_json::to_string_pretty(self).unwrap()
     }
-}
 
-pub fn get_config() -> Config {
-    unsafe { CONFIG.clone() }
-}
+    pub fn from_json(json: &str) -> Result<Config, ConfigError> {
+        let config: Config =
+            serde_json::from_str(json).map_err(|err| ConfigError::Format(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn as_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap()
+    }
 
-pub fn set_config(key: &str, value: &str) {
-    unsafe {
+    pub fn from_toml(source: &str) -> Result<Config, ConfigError> {
+        let config: Config =
+            toml::from_str(source).map_err(|err| ConfigError::Format(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads a config from a `.toml` or `.json` file, chosen by extension.
+    pub fn load(path: &std::path::Path) -> Result<Config, ConfigError> {
+        let source = std::fs::read_to_string(path).map_err(|err| ConfigError::Io(err.to_string()))?;
+        if is_toml(path) {
+            Config::from_toml(&source)
+        } else {
+            Config::from_json(&source)
+        }
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<(), ConfigError> {
+        let source = if is_toml(path) {
+            self.as_toml()
+        } else {
+            self.as_json()
+        };
+        std::fs::write(path, source).map_err(|err| ConfigError::Io(err.to_string()))
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<f64> {
+        match key {
+            "starting_pop" => Some(self.starting_pop as f64),
+            "width" => Some(self.width),
+            "height" => Some(self.height),
+            "max_creatures" => Some(self.max_creatures as f64),
+            "resolution" => Some(self.resolution),
+            "lifespan" => Some(self.lifespan as f64),
+            "reproduction_cooldown" => Some(self.reproduction_cooldown as f64),
+            "mate_cooldown" => Some(self.mate_cooldown as f64),
+            "breeding_age" => Some(self.breeding_age as f64),
+            "mate_search_radius" => Some(self.mate_search_radius),
+            "birth_rate_divisor" => Some(self.birth_rate_divisor as f64),
+            "corpse_persistence" => Some(self.corpse_persistence as f64),
+            "pedigree_capacity" => Some(self.pedigree_capacity as f64),
+            "colour_mutation_rate" => Some(self.colour_mutation_rate),
+            "speed_mutation_rate" => Some(self.speed_mutation_rate),
+            "dominance_mutation_rate" => Some(self.dominance_mutation_rate),
+            "polygene_mutation_rate" => Some(self.polygene_mutation_rate),
+            "additive_effect" => Some(self.additive_effect),
+            "environmental_noise" => Some(self.environmental_noise),
+            "vision_range" => Some(self.vision_range),
+            "slow_speed" => Some(self.slow_speed),
+            "medium_speed" => Some(self.medium_speed),
+            "fast_speed" => Some(self.fast_speed),
+            _ => None,
+        }
+    }
+
+    fn assign(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
         match key {
-            "starting_pop" => CONFIG.starting_pop = value.parse::<usize>().unwrap(),
-            "width" => CONFIG.width = value.parse::<f64>().unwrap(),
-            "height" => CONFIG.height = value.parse::<f64>().unwrap(),
-            "max_creatures" => CONFIG.max_creatures = value.parse::<usize>().unwrap(),
-            "resolution" => CONFIG.resolution = value.parse::<f64>().unwrap(),
-            _ => {}
+            "starting_pop" => self.starting_pop = parse(key, value)?,
+            "width" => self.width = parse(key, value)?,
+            "height" => self.height = parse(key, value)?,
+            "max_creatures" => self.max_creatures = parse(key, value)?,
+            "resolution" => self.resolution = parse(key, value)?,
+            "lifespan" => self.lifespan = parse(key, value)?,
+            "reproduction_cooldown" => self.reproduction_cooldown = parse(key, value)?,
+            "mate_cooldown" => self.mate_cooldown = parse(key, value)?,
+            "breeding_age" => self.breeding_age = parse(key, value)?,
+            "mate_search_radius" => self.mate_search_radius = parse(key, value)?,
+            "birth_rate_divisor" => self.birth_rate_divisor = parse(key, value)?,
+            "corpse_persistence" => self.corpse_persistence = parse(key, value)?,
+            "pedigree_capacity" => self.pedigree_capacity = parse(key, value)?,
+            "colour_mutation_rate" => self.colour_mutation_rate = parse(key, value)?,
+            "speed_mutation_rate" => self.speed_mutation_rate = parse(key, value)?,
+            "dominance_mutation_rate" => self.dominance_mutation_rate = parse(key, value)?,
+            "polygene_mutation_rate" => self.polygene_mutation_rate = parse(key, value)?,
+            "additive_effect" => self.additive_effect = parse(key, value)?,
+            "environmental_noise" => self.environmental_noise = parse(key, value)?,
+            "vision_range" => self.vision_range = parse(key, value)?,
+            "colour_dominance" => self.colour_dominance = parse(key, value)?,
+            "colour_order" => self.colour_order = parse_order(key, value)?,
+            "slow_speed" => self.slow_speed = parse(key, value)?,
+            "medium_speed" => self.medium_speed = parse(key, value)?,
+            "fast_speed" => self.fast_speed = parse(key, value)?,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Sets `key` from its string form, leaving the config untouched if the
+    /// result would be invalid.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        self.set_all([(key, value)])
+    }
+
+    /// Like `set`, but only validates once every pair has been applied, so
+    /// related settings can be changed together in any order.
+    pub fn set_all<'a>(
+        &mut self,
+        pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<(), ConfigError> {
+        let mut config = *self;
+        for (key, value) in pairs {
+            config.assign(key, value)?;
+        }
+        config.validate()?;
+        *self = config;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (key, min, max) in RANGES {
+            let value = self.get(key).unwrap();
+            if !(min..=max).contains(&value) {
+                return Err(ConfigError::OutOfRange {
+                    key: key.to_string(),
+                    value,
+                    min,
+                    max,
+                });
+            }
+        }
+        for (i, colour) in self.colour_order.iter().enumerate() {
+            if self.colour_order[..i].contains(colour) {
+                return Err(ConfigError::RepeatedColour(*colour));
+            }
+        }
+        if self.starting_pop > self.max_creatures {
+            return Err(ConfigError::StartingPopExceedsMax {
+                starting_pop: self.starting_pop,
+                max_creatures: self.max_creatures,
+            });
         }
+        Ok(())
     }
 }
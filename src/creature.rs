@@ -1,31 +1,24 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::{PI, TAU};
-use web_sys::CanvasRenderingContext2d;
 
-use crate::config::get_config;
+use crate::config::Config;
+use crate::genome::{Genome, Mutation};
+use crate::quantitative::{Traits, MEAN_TURN};
 
 use super::gene::*;
 use super::utils::*;
 
-static mut ID: u32 = 0;
-
-fn get_id() -> u32 {
-    unsafe {
-        ID += 1;
-        ID
-    }
-}
-
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Creature {
     pub id: u32,
     pub position: Point,
     pub direction: Direction,
-    pub colour_genes: [ColourGene; 2],
-    pub speed_genes: [Speed; 2],
-    pub colour: ColourGene,
+    pub genome: Genome,
     pub speed: Speed,
+    pub traits: Traits,
     pub is_alive: bool,
-    pub last_reproduced: u8,
+    pub last_reproduced: u32,
     pub parents: [u32; 2],
     pub offspring: Vec<u32>,
     last_turn: bool,
@@ -35,20 +28,21 @@ pub struct Creature {
 
 impl Creature {
     fn new(
+        id: u32,
         position: Point,
         direction: Direction,
-        colour_genes: [ColourGene; 2],
-        speed_genes: [Speed; 2],
+        genome: Genome,
+        config: &Config,
+        traits: Traits,
         parents: Option<[u32; 2]>,
     ) -> Creature {
         Creature {
-            id: get_id(),
+            id,
             position,
             direction,
-            colour_genes,
-            speed_genes,
-            colour: ColourGene::colour(colour_genes[0], colour_genes[1]),
-            speed: Speed::speed(speed_genes[0], speed_genes[1]),
+            genome,
+            speed: genome.speed(config),
+            traits,
             is_alive: true,
             last_reproduced: 0,
             parents: parents.unwrap_or([0, 0]),
@@ -59,56 +53,75 @@ impl Creature {
         }
     }
 
-    pub fn new_random() -> Creature {
-        Creature::new(
-            Point::new_random(),
-            rand::random::<Direction>() % TAU,
-            [ColourGene::new_random(), ColourGene::new_random()],
-            [Speed::new_random(), Speed::new_random()],
-            None,
-        )
+    pub fn new_random(id: u32, config: &Config, rng: &mut impl Rng) -> Creature {
+        let position = Point::new_random(rng, config.bounds());
+        let direction = rng.gen::<Direction>() % TAU;
+        let genome = Genome::random(rng);
+        let traits = Traits::express(&genome, config, rng);
+        Creature::new(id, position, direction, genome, config, traits, None)
     }
 
-    pub fn from_parents(parents: [&Creature; 2]) -> Creature {
-        Creature::new(
-            parents[0].position.midpoint(&parents[1].position),
-            rand::random::<Direction>(),
-            [
-                parents[0].colour_genes[rand::random::<usize>() % 2],
-                parents[1].colour_genes[rand::random::<usize>() % 2],
-            ],
-            [
-                parents[0].speed_genes[rand::random::<usize>() % 2],
-                parents[1].speed_genes[rand::random::<usize>() % 2],
-            ],
+    /// Breeds a child of `parents`, returning it along with any mutations to
+    /// the alleles it inherited, each paired with the ID of the parent the
+    /// allele came from.
+    pub fn from_parents(
+        id: u32,
+        parents: [&Creature; 2],
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> (Creature, Vec<(u32, Mutation)>) {
+        let position = parents[0]
+            .position
+            .torus_midpoint(&parents[1].position, config.bounds());
+        let direction = rng.gen::<Direction>();
+        let (genome, mutations) =
+            Genome::inherit(parents.map(|parent| &parent.genome), config, rng);
+        let traits = Traits::express(&genome, config, rng);
+        let creature = Creature::new(
+            id,
+            position,
+            direction,
+            genome,
+            config,
+            traits,
             Some([parents[0].id, parents[1].id]),
-        )
+        );
+        let mutations = mutations
+            .into_iter()
+            .map(|(parent, mutation)| (parents[parent].id, mutation))
+            .collect();
+        (creature, mutations)
     }
 
-    fn swim(&mut self) {
+    fn swim(&mut self, config: &Config) {
         let distance = match self.speed {
-            Speed::Fast => 2.5,
-            Speed::Medium(_) => 2.0,
-            Speed::Slow(_) => 1.5,
+            Speed::Fast => config.fast_speed,
+            Speed::Medium(_) => config.medium_speed,
+            Speed::Slow(_) => config.slow_speed,
         };
-        let distance = self.age.min(30) as f64 * distance / 30.0;
-        self.position.translate3(self.direction, distance);
+        let distance = self.age.min(30) as f64 * distance * self.traits.size / 30.0;
+        self.position.translate3(self.direction, distance, config.bounds());
     }
 
-    pub fn tick(&mut self, direction: Direction) {
+    /// Ages the creature and, while it lives, swims on, steering towards
+    /// `focus` if it is within sight.
+    pub fn tick(&mut self, focus: Point, config: &Config, rng: &mut impl Rng) {
         self.age += 1;
-        if self.age > get_config().lifespan {
+        if self.age > config.lifespan {
             self.is_alive = false;
         }
         if !self.is_alive {
             return;
         }
-        self.last_reproduced += 1;
-        
-        self.swim();
-        let turn = rand::random::<Direction>() % (PI / 32.0);
-        let r = rand::random::<u8>();
-        if ((self.direction < direction) != self.last_turn && r < 32) || r < 4 {
+        self.last_reproduced = self.last_reproduced.saturating_add(1);
+
+        self.swim(config);
+        let bounds = config.bounds();
+        let sees_focus = self.position.torus_distance(&focus, bounds) <= self.traits.vision;
+        let direction = self.position.torus_direction_to(&focus, bounds);
+        let turn = rng.gen::<Direction>() % MEAN_TURN * self.traits.turn_rate;
+        let r = rng.gen::<u8>();
+        if (sees_focus && (self.direction < direction) != self.last_turn && r < 32) || r < 4 {
             self.last_turn = !self.last_turn;
         }
         if self.last_turn {
@@ -119,46 +132,60 @@ impl Creature {
         self.direction %= TAU;
     }
 
-    fn body_positions(&self) -> (Point, Point, Point) {
+    fn body_positions(&self, bounds: Bounds) -> (Point, Point, Point) {
         (
-            self.position.translate4(PI / 2.0 + self.direction, 2.5),
-            self.position.translate4(-PI / 2.0 + self.direction, 2.5),
-            self.position.translate4(PI + self.direction, 3.0),
+            self.position.translate4(PI / 2.0 + self.direction, 2.5, bounds),
+            self.position.translate4(-PI / 2.0 + self.direction, 2.5, bounds),
+            self.position.translate4(PI + self.direction, 3.0, bounds),
         )
     }
 
-    pub fn render_selected(&mut self, context: &CanvasRenderingContext2d) {
-        let position = self.position.clone();
-        self.position = Point::new(20.0, 20.0);
-        // self.render(context);
-        self.position = position;
-    }
-
-    fn does_overlap(creature1: &Creature, creature2: &Creature) -> bool {
-        if creature1.position.distance(&creature2.position) > 10.0 {
+    pub fn does_overlap(creature1: &Creature, creature2: &Creature, bounds: Bounds) -> bool {
+        if creature1.position.torus_distance(&creature2.position, bounds) > 10.0 {
             return false;
         }
-        let (eye1, eye2, tail) = creature1.body_positions();
-        let (eye3, eye4, tail2) = creature2.body_positions();
-        if eye1.distance(&eye3) < 10.0
-            || eye1.distance(&eye4) < 10.0
-            || eye2.distance(&eye3) < 10.0
-            || eye2.distance(&eye4) < 10.0
+        let (eye1, eye2, tail) = creature1.body_positions(bounds);
+        let (eye3, eye4, tail2) = creature2.body_positions(bounds);
+        if eye1.torus_distance(&eye3, bounds) < 10.0
+            || eye1.torus_distance(&eye4, bounds) < 10.0
+            || eye2.torus_distance(&eye3, bounds) < 10.0
+            || eye2.torus_distance(&eye4, bounds) < 10.0
         {
             return true;
         };
-        if tail.distance(&eye3) < 10.0
-            || tail.distance(&eye4) < 10.0
-            || tail2.distance(&eye1) < 10.0
-            || tail2.distance(&eye2) < 10.0
-            || tail.distance(&tail2) < 10.0
+        if tail.torus_distance(&eye3, bounds) < 10.0
+            || tail.torus_distance(&eye4, bounds) < 10.0
+            || tail2.torus_distance(&eye1, bounds) < 10.0
+            || tail2.torus_distance(&eye2, bounds) < 10.0
+            || tail.torus_distance(&tail2, bounds) < 10.0
         {
             return true;
         };
         false
     }
 
-    pub fn get_info_as_json(&self) -> String {
-        format!("{{\"age\": {}, \"speed\": \"{}\", \"speed_genes\": \"{}/{}\", \"colour_genes\": \"{}/{}\", \"colour\": \"{:?}\", \"offspring\": {}, \"last_reproduced\": {}}}", self.age, self.speed, self.speed_genes[0], self.speed_genes[1], self.colour_genes[0], self.colour_genes[1], self.colour, self.offspring.len(), self.last_reproduced)
+    /// The creature's colour phenotype under the dominance model in `config`,
+    /// so changing the model recolours every creature at once.
+    pub fn skin(&self, config: &Config) -> Skin {
+        self.genome.colour(config)
+    }
+
+    pub fn get_info_as_json(&self, config: &Config) -> String {
+        let mut json = format!("{{\"age\": {}", self.age);
+        for locus in self.genome.expressed(config) {
+            json.push_str(&format!(
+                ", \"{0}\": \"{1}\", \"{0}_genes\": \"{2}\"",
+                locus.locus, locus.phenotype, locus.genotype
+            ));
+        }
+        for (name, value) in Traits::NAMES.iter().zip(self.traits.values()) {
+            json.push_str(&format!(", \"{}\": {:.3}", name, value));
+        }
+        json.push_str(&format!(
+            ", \"offspring\": {}, \"last_reproduced\": {}}}",
+            self.offspring.len(),
+            self.last_reproduced
+        ));
+        json
     }
 }
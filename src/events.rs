@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::genome::Mutation;
+use crate::utils::Point;
+use crate::world::World;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    /// Lived past `Config::lifespan`.
+    OldAge,
+}
+
+/// Something that happened to a creature, stamped with the tick it happened
+/// during.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Birth {
+        tick: u64,
+        id: u32,
+        parents: [u32; 2],
+        position: Point,
+    },
+    Death {
+        tick: u64,
+        id: u32,
+        cause: DeathCause,
+        age: u32,
+        offspring: usize,
+    },
+    /// An allele changed as it was passed from `parent` to the newborn `id`.
+    /// Reported straight after the birth.
+    Mutation {
+        tick: u64,
+        id: u32,
+        parent: u32,
+        #[serde(flatten)]
+        mutation: Mutation,
+    },
+    /// A corpse was cleared away after `Config::corpse_persistence` ticks.
+    CorpseRemoved { tick: u64, id: u32 },
+}
+
+/// Writes each event as one line of JSON.
+pub fn write_json_lines(writer: &mut impl Write, events: &[Event]) -> io::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut *writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+type Listener = Box<dyn FnMut(&Event) + Send>;
+
+/// Delivers a world's events to its listeners and, once enabled, a bounded
+/// buffer for polling. Neither is carried over when the world is cloned or
+/// saved.
+#[derive(Default)]
+pub(crate) struct EventBus {
+    listeners: Vec<Listener>,
+    buffer: VecDeque<Event>,
+    capacity: usize,
+}
+
+impl Clone for EventBus {
+    fn clone(&self) -> Self {
+        EventBus::default()
+    }
+}
+
+impl EventBus {
+    pub(crate) fn emit(&mut self, event: Event) {
+        for listener in &mut self.listeners {
+            listener(&event);
+        }
+        if self.capacity > 0 {
+            if self.buffer.len() == self.capacity {
+                self.buffer.pop_front();
+            }
+            self.buffer.push_back(event);
+        }
+    }
+}
+
+impl World {
+    /// Calls `listener` with every event from now on.
+    pub fn on_event(&mut self, listener: impl FnMut(&Event) + Send + 'static) {
+        self.events.listeners.push(Box::new(listener));
+    }
+
+    /// Starts keeping events for `drain_events`, up to `capacity` of them
+    /// with the oldest dropped first. A capacity of 0 stops buffering.
+    pub fn buffer_events(&mut self, capacity: usize) {
+        self.events.capacity = capacity;
+        while self.events.buffer.len() > capacity {
+            self.events.buffer.pop_front();
+        }
+    }
+
+    /// Takes every buffered event, oldest first.
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        self.events.buffer.drain(..).collect()
+    }
+}
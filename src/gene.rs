@@ -1,6 +1,74 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// A gene with a fixed set of alleles. Every creature carries two alleles of
+/// each locus in its `Genome`, one from each parent, and expresses a phenotype
+/// from the pair.
+pub trait Locus: Copy + Eq + Display + Serialize + DeserializeOwned + 'static {
+    /// Names the locus in stats, events and JSON.
+    const NAME: &'static str;
+    /// Every allele, in the order stats list them.
+    const ALLELES: &'static [Self];
+    type Phenotype: Copy + Eq + Display;
+
+    fn random(rng: &mut impl Rng) -> Self;
+
+    /// The phenotype of a creature carrying `first` and `second`.
+    fn express(first: Self, second: Self, config: &Config) -> Self::Phenotype;
+
+    /// Every phenotype `express` can give under `config`, in the order stats
+    /// count them. By default, those of every pair of alleles.
+    fn phenotypes(config: &Config) -> Vec<Self::Phenotype> {
+        let mut phenotypes = Vec::new();
+        for &first in Self::ALLELES {
+            for &second in Self::ALLELES {
+                let phenotype = Self::express(first, second, config);
+                if !phenotypes.contains(&phenotype) {
+                    phenotypes.push(phenotype);
+                }
+            }
+        }
+        phenotypes
+    }
+
+    /// A lower case name for a phenotype in column headers. Phenotypes with
+    /// the same name are counted together.
+    fn phenotype_key(phenotype: Self::Phenotype) -> String {
+        phenotype.to_string().to_lowercase()
+    }
+
+    /// The allele a child inherits from a parent carrying `alleles`.
+    // Picked with `gen::<bool>()` rather than a `usize` draw, as the latter
+    // consumes a different amount of randomness on 32-bit wasm.
+    fn transmit(alleles: [Self; 2], rng: &mut impl Rng) -> Self {
+        alleles[rng.gen::<bool>() as usize]
+    }
+
+    /// Possibly changes this allele on its way from parent to child, at the
+    /// rates in `config`, returning whether it did.
+    fn mutate(&mut self, config: &Config, rng: &mut impl Rng) -> bool;
+
+    /// Where this allele is in `ALLELES`.
+    fn index(self) -> usize {
+        Self::ALLELES
+            .iter()
+            .position(|&allele| allele == self)
+            .unwrap()
+    }
+
+    /// A lower case name for column headers.
+    fn key(self) -> String {
+        self.to_string().to_lowercase()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum ColourGene {
     Orange = 0,
     Red = 1,
@@ -12,9 +80,49 @@ pub enum ColourGene {
 }
 
 impl ColourGene {
-    pub fn new_random() -> ColourGene {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    pub const ALL: [ColourGene; 7] = [
+        ColourGene::Orange,
+        ColourGene::Red,
+        ColourGene::LBlue,
+        ColourGene::DBlue,
+        ColourGene::Black,
+        ColourGene::Yellow,
+        ColourGene::Purple,
+    ];
+
+    /// The name used in world info keys and the web page's element IDs.
+    pub fn name(self) -> &'static str {
+        match self {
+            ColourGene::Orange => "orange",
+            ColourGene::Red => "red",
+            ColourGene::LBlue => "light_blue",
+            ColourGene::DBlue => "dark_blue",
+            ColourGene::Black => "black",
+            ColourGene::Yellow => "yellow",
+            ColourGene::Purple => "purple",
+        }
+    }
+
+    /// The main body colour of this fish in `fish.png`, as `#rrggbb`.
+    pub fn hex(self) -> &'static str {
+        match self {
+            ColourGene::Orange => "#e04808",
+            ColourGene::Red => "#e00808",
+            ColourGene::LBlue => "#28a0c8",
+            ColourGene::DBlue => "#2018b0",
+            ColourGene::Black => "#181828",
+            ColourGene::Yellow => "#e0d008",
+            ColourGene::Purple => "#e008e0",
+        }
+    }
+}
+
+impl Locus for ColourGene {
+    const NAME: &'static str = "colour";
+    const ALLELES: &'static [ColourGene] = &ColourGene::ALL;
+    type Phenotype = Skin;
+
+    fn random(rng: &mut impl Rng) -> ColourGene {
         let x: u8 = rng.gen();
         match x % 7 {
             0 => ColourGene::Orange,
@@ -28,16 +136,161 @@ impl ColourGene {
         }
     }
 
-    pub fn colour(first: ColourGene, second: ColourGene) -> ColourGene {
-        if (first as u8) < (second as u8) {
-            first
-        } else {
-            second
+    /// Homozygotes are a solid colour. Heterozygotes depend on
+    /// `colour_dominance`, taking the colour earlier in `colour_order` under
+    /// complete dominance.
+    fn express(first: ColourGene, second: ColourGene, config: &Config) -> Skin {
+        if first == second {
+            return Skin::Solid(first);
         }
+        let pair = (first.min(second), first.max(second));
+        match config.colour_dominance {
+            Dominance::Complete => {
+                let rank = |colour| {
+                    config
+                        .colour_order
+                        .iter()
+                        .position(|&other| other == colour)
+                        .unwrap_or(ColourGene::ALL.len())
+                };
+                Skin::Solid(if rank(first) <= rank(second) { first } else { second })
+            }
+            Dominance::Codominant => Skin::Patched(pair.0, pair.1),
+            Dominance::Incomplete => Skin::Blended(pair.0, pair.1),
+        }
+    }
+
+    fn phenotypes(config: &Config) -> Vec<Skin> {
+        Skin::all(config.colour_dominance)
+    }
+
+    fn phenotype_key(skin: Skin) -> String {
+        skin.name()
+    }
+
+    /// Replaces this allele with a different colour with probability
+    /// `colour_mutation_rate`.
+    fn mutate(&mut self, config: &Config, rng: &mut impl Rng) -> bool {
+        let rate = config.colour_mutation_rate;
+        if rate <= 0.0 || !rng.gen_bool(rate) {
+            return false;
+        }
+        let offset = rng.gen::<u8>() % 6 + 1;
+        *self = ColourGene::ALL[(*self as usize + offset as usize) % 7];
+        true
+    }
+}
+
+/// Reads a colour by its name or lower case display name, e.g. `light_blue`
+/// or `lblue`.
+impl FromStr for ColourGene {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<ColourGene, ()> {
+        let s = s.trim().to_lowercase();
+        ColourGene::ALL
+            .into_iter()
+            .find(|colour| colour.name() == s || colour.key() == s)
+            .ok_or(())
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// How the two colour alleles of a heterozygote are expressed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dominance {
+    /// Only the allele earlier in `colour_order` shows.
+    #[default]
+    Complete,
+    /// Both alleles show, as patches of one colour on the other.
+    Codominant,
+    /// The creature is a colour between the two.
+    Incomplete,
+}
+
+impl FromStr for Dominance {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Dominance, ()> {
+        match s.trim() {
+            "complete" => Ok(Dominance::Complete),
+            "codominant" => Ok(Dominance::Codominant),
+            "incomplete" => Ok(Dominance::Incomplete),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The colour phenotype. Heterozygous skins list their colours in
+/// `ColourGene::ALL` order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Skin {
+    Solid(ColourGene),
+    /// Codominance: the first colour with patches of the second.
+    Patched(ColourGene, ColourGene),
+    /// Incomplete dominance: halfway between the two colours.
+    Blended(ColourGene, ColourGene),
+}
+
+impl Skin {
+    /// Every skin a creature can have under `dominance`: the solid colours,
+    /// then each pair of colours if heterozygotes show both.
+    pub fn all(dominance: Dominance) -> Vec<Skin> {
+        let mut skins: Vec<Skin> = ColourGene::ALL.into_iter().map(Skin::Solid).collect();
+        let pair: fn(ColourGene, ColourGene) -> Skin = match dominance {
+            Dominance::Complete => return skins,
+            Dominance::Codominant => Skin::Patched,
+            Dominance::Incomplete => Skin::Blended,
+        };
+        for (i, first) in ColourGene::ALL.into_iter().enumerate() {
+            for second in ColourGene::ALL.into_iter().skip(i + 1) {
+                skins.push(pair(first, second));
+            }
+        }
+        skins
+    }
+
+    /// A lower case name for world info keys, column headers and element
+    /// IDs, e.g. `orange`, `orange_red_patched` or `orange_red_blended`.
+    pub fn name(self) -> String {
+        match self {
+            Skin::Solid(colour) => colour.name().to_string(),
+            Skin::Patched(first, second) => format!("{}_{}_patched", first.name(), second.name()),
+            Skin::Blended(first, second) => format!("{}_{}_blended", first.name(), second.name()),
+        }
+    }
+
+    /// The colour a swatch of this skin is drawn in, as `#rrggbb`. Patched
+    /// skins give their main colour.
+    pub fn hex(self) -> String {
+        match self {
+            Skin::Solid(colour) | Skin::Patched(colour, _) => colour.hex().to_string(),
+            Skin::Blended(first, second) => {
+                let rgb = |colour: ColourGene| u32::from_str_radix(&colour.hex()[1..], 16).unwrap();
+                let (first, second) = (rgb(first), rgb(second));
+                let mix = (0..3).fold(0, |mix, channel| {
+                    let shift = channel * 8;
+                    let mean = ((first >> shift & 0xff) + (second >> shift & 0xff)) / 2;
+                    mix | mean << shift
+                });
+                format!("#{:06x}", mix)
+            }
+        }
+    }
+}
+
+/// Writes patched skins as `Orange+Red` and blended ones as `Orange~Red`.
+impl Display for Skin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Skin::Solid(colour) => write!(f, "{}", colour),
+            Skin::Patched(first, second) => write!(f, "{}+{}", first, second),
+            Skin::Blended(first, second) => write!(f, "{}~{}", first, second),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Speed {
     Slow(bool),
     Medium(bool),
@@ -45,9 +298,57 @@ pub enum Speed {
 }
 
 impl Speed {
-    pub fn new_random() -> Speed {
-        let x: u8 = rand::random();
-        let y: bool = rand::random();
+    /// Every allele, with the dominance flag telling apart otherwise equal
+    /// slow and medium alleles.
+    pub const ALL: [Speed; 5] = [
+        Speed::Slow(true),
+        Speed::Slow(false),
+        Speed::Medium(true),
+        Speed::Medium(false),
+        Speed::Fast,
+    ];
+
+    /// Moves this allele to another of slow, medium and fast with probability
+    /// `rate`. A fast allele becoming slow or medium takes a random dominance.
+    fn mutate_class(&mut self, rate: f64, rng: &mut impl Rng) -> bool {
+        if rate <= 0.0 || !rng.gen_bool(rate) {
+            return false;
+        }
+        let other = rng.gen::<bool>();
+        *self = match *self {
+            Speed::Slow(_) | Speed::Medium(_) if other => Speed::Fast,
+            Speed::Slow(dominant) => Speed::Medium(dominant),
+            Speed::Medium(dominant) => Speed::Slow(dominant),
+            Speed::Fast if other => Speed::Slow(rng.gen()),
+            Speed::Fast => Speed::Medium(rng.gen()),
+        };
+        true
+    }
+
+    /// Flips the dominance of a slow or medium allele with probability
+    /// `rate`. Fast alleles have no dominance to flip.
+    fn mutate_dominance(&mut self, rate: f64, rng: &mut impl Rng) -> bool {
+        let flipped = match *self {
+            Speed::Slow(dominant) => Speed::Slow(!dominant),
+            Speed::Medium(dominant) => Speed::Medium(!dominant),
+            Speed::Fast => return false,
+        };
+        if rate <= 0.0 || !rng.gen_bool(rate) {
+            return false;
+        }
+        *self = flipped;
+        true
+    }
+}
+
+impl Locus for Speed {
+    const NAME: &'static str = "speed";
+    const ALLELES: &'static [Speed] = &Speed::ALL;
+    type Phenotype = Speed;
+
+    fn random(rng: &mut impl Rng) -> Speed {
+        let x: u8 = rng.gen();
+        let y: bool = rng.gen();
         match x % 3 {
             0 => Speed::Slow(y),
             1 => Speed::Medium(y),
@@ -56,7 +357,7 @@ impl Speed {
         }
     }
 
-    pub fn speed(first: Speed, second: Speed) -> Speed {
+    fn express(first: Speed, second: Speed, _: &Config) -> Speed {
         match (first, second) {
             (Speed::Fast, Speed::Fast) => Speed::Fast,
             (Speed::Fast, _) => first,
@@ -64,25 +365,125 @@ impl Speed {
             (Speed::Slow(_), Speed::Slow(_)) => first,
             (Speed::Medium(_), Speed::Medium(_)) => first,
             (Speed::Slow(a), Speed::Medium(b)) => {
-                if a == b {
-                    first
-                } else if a {
+                if a == b || a {
                     first
                 } else {
                     second
                 }
             },
             (Speed::Medium(a), Speed::Slow(b)) => {
-                if a == b {
+                if a == b || !a {
                     second
-                } else if a {
-                    first
                 } else {
-                    second
+                    first
                 }
             }
         }
     }
+
+    /// Every allele is the phenotype of its homozygote.
+    fn phenotypes(_: &Config) -> Vec<Speed> {
+        Speed::ALL.to_vec()
+    }
+
+    /// Slow, medium or fast, whatever the dominance.
+    fn phenotype_key(speed: Speed) -> String {
+        match speed {
+            Speed::Slow(_) => "slow",
+            Speed::Medium(_) => "medium",
+            Speed::Fast => "fast",
+        }
+        .to_string()
+    }
+
+    /// A class change at `speed_mutation_rate`, then a dominance flip at
+    /// `dominance_mutation_rate`.
+    fn mutate(&mut self, config: &Config, rng: &mut impl Rng) -> bool {
+        let class = self.mutate_class(config.speed_mutation_rate, rng);
+        let dominance = self.mutate_dominance(config.dominance_mutation_rate, rng);
+        class || dominance
+    }
+
+    fn key(self) -> String {
+        match self {
+            Speed::Slow(true) => "slow_d",
+            Speed::Slow(false) => "slow_r",
+            Speed::Medium(true) => "medium_d",
+            Speed::Medium(false) => "medium_r",
+            Speed::Fast => "fast",
+        }
+        .to_string()
+    }
+}
+
+/// How many loci lie behind each quantitative trait. `Genome` names each
+/// locus in `random`, `inherit` and `visit`, so changing this means adding or
+/// removing lines there and in `QTL_NAMES`.
+pub const POLYGENE_LOCI: usize = 4;
+
+const QTL_NAMES: [[&str; POLYGENE_LOCI]; 3] = [
+    ["size_1", "size_2", "size_3", "size_4"],
+    ["turn_rate_1", "turn_rate_2", "turn_rate_3", "turn_rate_4"],
+    ["vision_1", "vision_2", "vision_3", "vision_4"],
+];
+
+/// An allele at locus `LOCUS` of those behind quantitative trait `TRAIT`,
+/// `true` for the allele that increases the trait. Every locus is its own
+/// type, so each is inherited and reported separately.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Qtl<const TRAIT: usize, const LOCUS: usize>(pub bool);
+
+impl<const TRAIT: usize, const LOCUS: usize> Locus for Qtl<TRAIT, LOCUS> {
+    const NAME: &'static str = QTL_NAMES[TRAIT][LOCUS];
+    const ALLELES: &'static [Qtl<TRAIT, LOCUS>] = &[Qtl(false), Qtl(true)];
+    /// The number of increasing alleles, which the trait's value rises with.
+    type Phenotype = u32;
+
+    fn random(rng: &mut impl Rng) -> Qtl<TRAIT, LOCUS> {
+        Qtl(rng.gen())
+    }
+
+    fn express(first: Qtl<TRAIT, LOCUS>, second: Qtl<TRAIT, LOCUS>, _: &Config) -> u32 {
+        first.0 as u32 + second.0 as u32
+    }
+
+    /// Swaps this allele for the other with probability
+    /// `polygene_mutation_rate`.
+    fn mutate(&mut self, config: &Config, rng: &mut impl Rng) -> bool {
+        let rate = config.polygene_mutation_rate;
+        if rate <= 0.0 || !rng.gen_bool(rate) {
+            return false;
+        }
+        self.0 = !self.0;
+        true
+    }
+
+    fn key(self) -> String {
+        format!("{}_{}", Self::NAME, if self.0 { "plus" } else { "minus" })
+    }
+}
+
+/// Writes `+` for the increasing allele and `-` for the other.
+impl<const TRAIT: usize, const LOCUS: usize> Display for Qtl<TRAIT, LOCUS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", if self.0 { '+' } else { '-' })
+    }
+}
+
+/// Both alleles at every locus behind one quantitative trait, `true` for
+/// those that increase it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Polygenes(pub [[bool; 2]; POLYGENE_LOCI]);
+
+impl Polygenes {
+    pub fn random(rng: &mut impl Rng) -> Polygenes {
+        Polygenes([(); POLYGENE_LOCI].map(|_| [rng.gen(), rng.gen()]))
+    }
+
+    /// How many of the alleles, across every locus, increase the trait.
+    pub fn increasing(&self) -> u32 {
+        self.0.iter().flatten().filter(|&&allele| allele).count() as u32
+    }
 }
 
 impl Display for Speed {
@@ -107,4 +508,4 @@ impl Display for ColourGene {
             ColourGene::Purple => write!(f, "Purple"),
         }
     }
-}
\ No newline at end of file
+}
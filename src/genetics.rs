@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::gene::Locus;
+use crate::genome::{Genome, LocusVisitor};
+use crate::quantitative::Traits;
+use crate::world::World;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AlleleFrequency {
+    pub allele: String,
+    pub frequency: f64,
+}
+
+/// How common an unordered pair of alleles is, against what Hardy–Weinberg
+/// equilibrium predicts from the allele frequencies.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GenotypeFrequency {
+    pub genotype: String,
+    pub count: usize,
+    pub observed: f64,
+    pub expected: f64,
+}
+
+/// Allele and genotype frequencies at one locus. Alleles and genotypes
+/// missing from the population are left out.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LocusStats {
+    pub alleles: Vec<AlleleFrequency>,
+    pub genotypes: Vec<GenotypeFrequency>,
+    pub observed_heterozygosity: f64,
+    /// `1 - Σp²`, the heterozygosity expected under Hardy–Weinberg.
+    pub expected_heterozygosity: f64,
+    /// Pearson's χ² of the genotype counts against Hardy–Weinberg, with
+    /// `k(k-1)/2` degrees of freedom for `k` alleles.
+    pub chi_squared: f64,
+    pub degrees_of_freedom: usize,
+}
+
+impl LocusStats {
+    /// Tallies `genotypes`, given as indices into `alleles`.
+    fn new<A: Display>(
+        alleles: &[A],
+        genotypes: impl IntoIterator<Item = [usize; 2]>,
+    ) -> LocusStats {
+        let k = alleles.len();
+        let mut allele_counts = vec![0; k];
+        let mut genotype_counts = vec![vec![0; k]; k];
+        let mut individuals = 0;
+        for [a, b] in genotypes {
+            individuals += 1;
+            allele_counts[a] += 1;
+            allele_counts[b] += 1;
+            genotype_counts[a.min(b)][a.max(b)] += 1;
+        }
+        let n = individuals as f64;
+        let p: Vec<f64> = allele_counts
+            .iter()
+            .map(|&count| if individuals == 0 { 0.0 } else { count as f64 / (2.0 * n) })
+            .collect();
+
+        let mut locus = LocusStats {
+            alleles: Vec::new(),
+            genotypes: Vec::new(),
+            observed_heterozygosity: 0.0,
+            expected_heterozygosity: 0.0,
+            chi_squared: 0.0,
+            degrees_of_freedom: 0,
+        };
+        if individuals == 0 {
+            return locus;
+        }
+        let present: Vec<usize> = (0..k).filter(|&i| allele_counts[i] > 0).collect();
+        for &i in &present {
+            locus.alleles.push(AlleleFrequency {
+                allele: alleles[i].to_string(),
+                frequency: p[i],
+            });
+            locus.expected_heterozygosity -= p[i] * p[i];
+        }
+        locus.expected_heterozygosity += 1.0;
+        for (x, &i) in present.iter().enumerate() {
+            for &j in &present[x..] {
+                let count = genotype_counts[i][j];
+                let observed = count as f64 / n;
+                let expected = if i == j { p[i] * p[i] } else { 2.0 * p[i] * p[j] };
+                if i != j {
+                    locus.observed_heterozygosity += observed;
+                }
+                locus.chi_squared += (count as f64 - expected * n).powi(2) / (expected * n);
+                locus.genotypes.push(GenotypeFrequency {
+                    genotype: format!("{}/{}", alleles[i], alleles[j]),
+                    count,
+                    observed,
+                    expected,
+                });
+            }
+        }
+        locus.degrees_of_freedom = present.len() * (present.len() - 1) / 2;
+        locus
+    }
+}
+
+/// How a quantitative trait varies, and how much of that is down to genes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TraitStats {
+    pub mean: f64,
+    /// The variance of the values the creatures' genes alone would give.
+    pub genetic_variance: f64,
+    pub phenotypic_variance: f64,
+    /// Narrow-sense heritability, the genetic over the phenotypic variance.
+    /// The loci are purely additive, so all the genetic variance is additive.
+    pub heritability: f64,
+}
+
+impl TraitStats {
+    fn new(genotypic: &[f64], phenotypic: &[f64]) -> TraitStats {
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+        let variance = |values: &[f64]| {
+            let mean = mean(values);
+            values.iter().map(|value| (value - mean).powi(2)).sum::<f64>()
+                / values.len().max(1) as f64
+        };
+        let genetic_variance = variance(genotypic);
+        let phenotypic_variance = variance(phenotypic);
+        TraitStats {
+            mean: mean(phenotypic),
+            genetic_variance,
+            phenotypic_variance,
+            heritability: if phenotypic_variance > 0.0 {
+                genetic_variance / phenotypic_variance
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Population genetics of the living creatures at every locus, keyed by
+/// `Locus::NAME`, and of each quantitative trait.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Genetics {
+    pub individuals: usize,
+    #[serde(flatten)]
+    pub loci: BTreeMap<&'static str, LocusStats>,
+    pub traits: BTreeMap<&'static str, TraitStats>,
+}
+
+impl World {
+    pub fn genetics(&self) -> Genetics {
+        struct Tally<'a> {
+            living: Vec<&'a Genome>,
+            loci: BTreeMap<&'static str, LocusStats>,
+        }
+
+        impl LocusVisitor for Tally<'_> {
+            fn visit<L: Locus>(&mut self, alleles: fn(&Genome) -> [L; 2]) {
+                let genotypes = self
+                    .living
+                    .iter()
+                    .map(|genome| alleles(genome).map(L::index));
+                self.loci.insert(L::NAME, LocusStats::new(L::ALLELES, genotypes));
+            }
+        }
+
+        let mut tally = Tally {
+            living: self
+                .creatures
+                .iter()
+                .filter(|creature| creature.is_alive)
+                .map(|creature| &creature.genome)
+                .collect(),
+            loci: BTreeMap::new(),
+        };
+        Genome::visit(&mut tally);
+
+        let living: Vec<_> = self.creatures.iter().filter(|creature| creature.is_alive).collect();
+        let genotypic: Vec<_> = living
+            .iter()
+            .map(|creature| Traits::genotypic(&creature.genome, &self.config).values())
+            .collect();
+        let traits = Traits::NAMES
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| {
+                let genotypic: Vec<_> = genotypic.iter().map(|values| values[i]).collect();
+                let phenotypic: Vec<_> =
+                    living.iter().map(|creature| creature.traits.values()[i]).collect();
+                (name, TraitStats::new(&genotypic, &phenotypic))
+            })
+            .collect();
+        Genetics {
+            individuals: tally.living.len(),
+            loci: tally.loci,
+            traits,
+        }
+    }
+}
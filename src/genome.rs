@@ -0,0 +1,185 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gene::{ColourGene, Locus, Polygenes, Qtl, Skin, Speed};
+
+/// The two alleles a creature carries at every locus, the first from its
+/// first parent and the second from its second.
+///
+/// A new locus needs a `Locus` implementation, a field here and a line in
+/// each of `random`, `inherit` and `visit`. Inheritance, mutation, events,
+/// stats columns, genetics and info JSON pick it up from those. The new field
+/// changes the saved genome, so `SNAPSHOT_VERSION` needs a bump too, and the
+/// phenotype only changes how a creature behaves or looks once `Creature`
+/// or the renderers use it, as they do `Speed` and `Skin`. The loci behind
+/// each quantitative trait are kept together in a `Polygenes` field, but
+/// visited one at a time.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub colour: [ColourGene; 2],
+    pub speed: [Speed; 2],
+    pub size: Polygenes,
+    pub turn_rate: Polygenes,
+    pub vision: Polygenes,
+}
+
+/// Something done with every locus in turn, see `Genome::visit`.
+pub trait LocusVisitor {
+    /// Called once per locus with a function reading its alleles from any
+    /// genome.
+    fn visit<L: Locus>(&mut self, alleles: fn(&Genome) -> [L; 2]);
+}
+
+/// A change to an allele on its way from parent to child.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mutation {
+    pub locus: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// One locus of a genome written out, e.g. `colour`, `Red/Orange` and `Orange`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expressed {
+    pub locus: &'static str,
+    pub genotype: String,
+    pub phenotype: String,
+}
+
+impl Genome {
+    pub fn random(rng: &mut impl Rng) -> Genome {
+        Genome {
+            colour: [ColourGene::random(rng), ColourGene::random(rng)],
+            speed: [Speed::random(rng), Speed::random(rng)],
+            size: Polygenes::random(rng),
+            turn_rate: Polygenes::random(rng),
+            vision: Polygenes::random(rng),
+        }
+    }
+
+    /// Takes an allele from each parent at every locus, as `Locus::transmit`
+    /// picks it, and gives each the chance to mutate. Mutations are returned
+    /// along with the index in `parents` of the parent the allele came from.
+    pub fn inherit(
+        parents: [&Genome; 2],
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> (Genome, Vec<(usize, Mutation)>) {
+        fn locus<L: Locus>(
+            parents: [[L; 2]; 2],
+            config: &Config,
+            rng: &mut impl Rng,
+            mutations: &mut Vec<(usize, Mutation)>,
+        ) -> [L; 2] {
+            let mut alleles = parents.map(|alleles| L::transmit(alleles, rng));
+            for (parent, allele) in alleles.iter_mut().enumerate() {
+                let from = *allele;
+                if allele.mutate(config, rng) && *allele != from {
+                    mutations.push((
+                        parent,
+                        Mutation {
+                            locus: L::NAME.to_string(),
+                            from: from.to_string(),
+                            to: allele.to_string(),
+                        },
+                    ));
+                }
+            }
+            alleles
+        }
+
+        fn polygenes<const TRAIT: usize>(
+            parents: [&Genome; 2],
+            config: &Config,
+            rng: &mut impl Rng,
+            mutations: &mut Vec<(usize, Mutation)>,
+        ) -> Polygenes {
+            Polygenes([
+                locus(parents.map(qtl::<TRAIT, 0>), config, rng, mutations).map(|allele| allele.0),
+                locus(parents.map(qtl::<TRAIT, 1>), config, rng, mutations).map(|allele| allele.0),
+                locus(parents.map(qtl::<TRAIT, 2>), config, rng, mutations).map(|allele| allele.0),
+                locus(parents.map(qtl::<TRAIT, 3>), config, rng, mutations).map(|allele| allele.0),
+            ])
+        }
+
+        let mut mutations = Vec::new();
+        let genome = Genome {
+            colour: locus(parents.map(|g| g.colour), config, rng, &mut mutations),
+            speed: locus(parents.map(|g| g.speed), config, rng, &mut mutations),
+            size: polygenes::<0>(parents, config, rng, &mut mutations),
+            turn_rate: polygenes::<1>(parents, config, rng, &mut mutations),
+            vision: polygenes::<2>(parents, config, rng, &mut mutations),
+        };
+        (genome, mutations)
+    }
+
+    pub fn visit(visitor: &mut impl LocusVisitor) {
+        fn polygenes<const TRAIT: usize>(visitor: &mut impl LocusVisitor) {
+            visitor.visit(qtl::<TRAIT, 0>);
+            visitor.visit(qtl::<TRAIT, 1>);
+            visitor.visit(qtl::<TRAIT, 2>);
+            visitor.visit(qtl::<TRAIT, 3>);
+        }
+
+        visitor.visit(|genome| genome.colour);
+        visitor.visit(|genome| genome.speed);
+        polygenes::<0>(visitor);
+        polygenes::<1>(visitor);
+        polygenes::<2>(visitor);
+    }
+
+    /// The name of every locus, in `visit` order.
+    pub fn loci() -> Vec<&'static str> {
+        struct Names(Vec<&'static str>);
+
+        impl LocusVisitor for Names {
+            fn visit<L: Locus>(&mut self, _: fn(&Genome) -> [L; 2]) {
+                self.0.push(L::NAME);
+            }
+        }
+
+        let mut names = Names(Vec::new());
+        Genome::visit(&mut names);
+        names.0
+    }
+
+    pub fn colour(&self, config: &Config) -> Skin {
+        ColourGene::express(self.colour[0], self.colour[1], config)
+    }
+
+    pub fn speed(&self, config: &Config) -> Speed {
+        Speed::express(self.speed[0], self.speed[1], config)
+    }
+
+    /// Every locus as text, in `visit` order.
+    pub fn expressed(&self, config: &Config) -> Vec<Expressed> {
+        struct Writer<'a>(&'a Genome, &'a Config, Vec<Expressed>);
+
+        impl LocusVisitor for Writer<'_> {
+            fn visit<L: Locus>(&mut self, alleles: fn(&Genome) -> [L; 2]) {
+                let [first, second] = alleles(self.0);
+                self.2.push(Expressed {
+                    locus: L::NAME,
+                    genotype: format!("{}/{}", first, second),
+                    phenotype: L::express(first, second, self.1).to_string(),
+                });
+            }
+        }
+
+        let mut writer = Writer(self, config, Vec::new());
+        Genome::visit(&mut writer);
+        writer.2
+    }
+}
+
+/// The alleles at locus `LOCUS` of those behind quantitative trait `TRAIT`,
+/// numbered as in `Traits::NAMES`.
+fn qtl<const TRAIT: usize, const LOCUS: usize>(genome: &Genome) -> [Qtl<TRAIT, LOCUS>; 2] {
+    let polygenes = match TRAIT {
+        0 => &genome.size,
+        1 => &genome.turn_rate,
+        _ => &genome.vision,
+    };
+    polygenes.0[LOCUS].map(Qtl)
+}
@@ -0,0 +1,89 @@
+use crate::utils::{Bounds, Point};
+
+/// A uniform grid of buckets holding indices into a slice of positions, used
+/// to find nearby creatures without scanning the whole population. Cells
+/// wrap around the edges of the world in the same way as `Point::wrap`.
+#[derive(Clone, Debug, Default)]
+pub struct SpatialGrid {
+    bounds: Option<Bounds>,
+    columns: usize,
+    rows: usize,
+    cell_width: f64,
+    cell_height: f64,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid whose cells are at least `cell_size` across.
+    pub fn new(bounds: Bounds, cell_size: f64) -> SpatialGrid {
+        let columns = ((bounds.width / cell_size) as usize).max(1);
+        let rows = ((bounds.height / cell_size) as usize).max(1);
+        SpatialGrid {
+            bounds: Some(bounds),
+            columns,
+            rows,
+            cell_width: bounds.width / columns as f64,
+            cell_height: bounds.height / rows as f64,
+            cells: vec![Vec::new(); columns * rows],
+        }
+    }
+
+    /// Refills the grid from `positions`, resizing it first if the world size
+    /// or cell size has changed. Index `i` in queries refers to `positions[i]`.
+    pub fn rebuild(
+        &mut self,
+        bounds: Bounds,
+        cell_size: f64,
+        positions: impl IntoIterator<Item = Point>,
+    ) {
+        let columns = ((bounds.width / cell_size) as usize).max(1);
+        let rows = ((bounds.height / cell_size) as usize).max(1);
+        if self.bounds != Some(bounds) || self.columns != columns || self.rows != rows {
+            *self = SpatialGrid::new(bounds, cell_size);
+        } else {
+            for cell in &mut self.cells {
+                cell.clear();
+            }
+        }
+        for (index, position) in positions.into_iter().enumerate() {
+            self.insert(index, position);
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: Point) {
+        let column = self.column(position.x);
+        let row = self.row(position.y);
+        self.cells[row * self.columns + column].push(index);
+    }
+
+    fn column(&self, x: f64) -> usize {
+        ((x / self.cell_width).floor() as isize).rem_euclid(self.columns as isize) as usize
+    }
+
+    fn row(&self, y: f64) -> usize {
+        ((y / self.cell_height).floor() as isize).rem_euclid(self.rows as isize) as usize
+    }
+
+    /// Yields every index in the cells overlapping the square of half-width
+    /// `radius` around `centre`, including cells across the world's edges.
+    /// Callers still need to check the actual distance of each candidate.
+    pub fn query(&self, centre: Point, radius: f64) -> impl Iterator<Item = usize> + '_ {
+        let columns = Self::span(centre.x, radius, self.cell_width, self.columns);
+        let rows = Self::span(centre.y, radius, self.cell_height, self.rows);
+        rows.flat_map(move |row| {
+            columns.clone().flat_map(move |column| {
+                let row = row.rem_euclid(self.rows as isize) as usize;
+                let column = column.rem_euclid(self.columns as isize) as usize;
+                self.cells[row * self.columns + column].iter().copied()
+            })
+        })
+    }
+
+    /// The unwrapped cell coordinates covered by `centre ± radius`, limited to
+    /// one lap of the grid so no cell is visited twice.
+    fn span(centre: f64, radius: f64, cell: f64, count: usize) -> std::ops::Range<isize> {
+        let first = ((centre - radius) / cell).floor() as isize;
+        let last = ((centre + radius) / cell).floor() as isize;
+        first..last.saturating_add(1).min(first.saturating_add(count as isize))
+    }
+}
@@ -1,9 +1,450 @@
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+
+use evolution_simulated::config::Config;
+use evolution_simulated::events::write_json_lines;
+use evolution_simulated::stats::{Stats, DEFAULT_CAPACITY};
+#[cfg(feature = "raster")]
+use evolution_simulated::render::raster::Sprites;
+#[cfg(feature = "raster")]
+use evolution_simulated::render::record::Recorder;
 use evolution_simulated::world::World;
 
+const USAGE: &str = "Usage: evolution-simulated [options]
+
+Options:
+    --ticks <n>           Number of ticks to simulate (default 1000)
+    --config <path>       Load settings from a .toml or .json file; other options override it
+    --save-config <path>  Write the effective settings to a .toml or .json file
+    --population <n>      Starting population
+    --width <n>           World width
+    --height <n>          World height
+    --lifespan <n>        Ticks a creature lives for
+    --max-creatures <n>   Population cap above which breeding stops
+    --set <key>=<value>   Set any other config value, e.g. --set breeding_age=40
+    --seed <n>            Seed for the random number generator of a new world, not with --load
+    --load <path>         Resume from a world snapshot instead of spawning a new world
+    --save <path>         Write a world snapshot when the run finishes
+    --svg <path>          Write an SVG picture of the world when the run finishes
+    --stats <path>        Write population statistics over time to a .csv or .json file
+    --stats-every <n>     Sample statistics every n ticks (default 1)
+    --events <path>       Write every birth, mutation, death and corpse removal as JSON lines
+    --graphml <path>      Write the pedigree of every creature as GraphML when the run finishes
+    --newick <path>       Write the first-parent lineages as Newick trees when the run finishes
+    --progress <n>        Print progress every n ticks, 0 to disable (default 100)
+    --frames <ticks>      Write a PNG of the world at each of these ticks, e.g. 0,100,500
+    --frames-dir <dir>    Where to write frames (default .)
+    --record <path>       Record the run into a .gif, or as numbered PNGs in a directory
+    --record-every <n>    Record every nth tick (default 1)
+    --assets <dir>        Where to find the sprite sheets (default web/assets)
+    -h, --help            Print this message";
+
+struct Args {
+    config_path: Option<PathBuf>,
+    settings: Vec<(String, String)>,
+    save_config: Option<PathBuf>,
+    load: Option<PathBuf>,
+    save: Option<PathBuf>,
+    svg: Option<PathBuf>,
+    stats: Option<PathBuf>,
+    stats_every: u32,
+    events: Option<PathBuf>,
+    graphml: Option<PathBuf>,
+    newick: Option<PathBuf>,
+    ticks: u32,
+    progress: u32,
+    seed: Option<u64>,
+    frames: Vec<u32>,
+    frames_dir: PathBuf,
+    record: Option<PathBuf>,
+    record_every: u32,
+    assets: PathBuf,
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("missing value for {}", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        config_path: None,
+        settings: Vec::new(),
+        save_config: None,
+        load: None,
+        save: None,
+        svg: None,
+        stats: None,
+        stats_every: 1,
+        events: None,
+        graphml: None,
+        newick: None,
+        ticks: 1000,
+        progress: 100,
+        seed: None,
+        frames: Vec::new(),
+        frames_dir: PathBuf::from("."),
+        record: None,
+        record_every: 1,
+        assets: PathBuf::from("web/assets"),
+    };
+    let mut argv = env::args().skip(1);
+    while let Some(flag) = argv.next() {
+        let key = match flag.as_str() {
+            "--ticks" => {
+                args.ticks = parse_value(&flag, argv.next())?;
+                continue;
+            }
+            "--progress" => {
+                args.progress = parse_value(&flag, argv.next())?;
+                continue;
+            }
+            "--config" => {
+                args.config_path = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--save-config" => {
+                args.save_config = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--load" => {
+                args.load = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--save" => {
+                args.save = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--svg" => {
+                args.svg = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--stats" => {
+                args.stats = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--stats-every" => {
+                args.stats_every = parse_value(&flag, argv.next())?;
+                if args.stats_every == 0 {
+                    return Err(format!("{} must be at least 1", flag));
+                }
+                continue;
+            }
+            "--events" => {
+                args.events = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--graphml" => {
+                args.graphml = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--newick" => {
+                args.newick = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--seed" => {
+                args.seed = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--frames" => {
+                let ticks = argv.next().ok_or(format!("missing value for {}", flag))?;
+                for tick in ticks.split(',') {
+                    args.frames.push(parse_value(&flag, Some(tick.trim().to_string()))?);
+                }
+                continue;
+            }
+            "--frames-dir" => {
+                args.frames_dir = parse_value(&flag, argv.next())?;
+                continue;
+            }
+            "--record" => {
+                args.record = Some(parse_value(&flag, argv.next())?);
+                continue;
+            }
+            "--record-every" => {
+                args.record_every = parse_value(&flag, argv.next())?;
+                if args.record_every == 0 {
+                    return Err(format!("{} must be at least 1", flag));
+                }
+                continue;
+            }
+            "--assets" => {
+                args.assets = parse_value(&flag, argv.next())?;
+                continue;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--set" => {
+                let setting = argv.next().ok_or(format!("missing value for {}", flag))?;
+                let (key, value) = setting
+                    .split_once('=')
+                    .ok_or(format!("expected <key>=<value> for {}, got {}", flag, setting))?;
+                args.settings.push((key.to_string(), value.to_string()));
+                continue;
+            }
+            "--population" => "starting_pop",
+            "--width" => "width",
+            "--height" => "height",
+            "--lifespan" => "lifespan",
+            "--max-creatures" => "max_creatures",
+            _ => return Err(format!("unknown argument: {}", flag)),
+        };
+        let value = argv.next().ok_or(format!("missing value for {}", flag))?;
+        args.settings.push((key.to_string(), value));
+    }
+    if args.load.is_some() && args.seed.is_some() {
+        return Err(
+            "--seed can't be used with --load, which carries on with the snapshot's own seed"
+                .to_string(),
+        );
+    }
+    Ok(args)
+}
+
+/// Loads or spawns the world, then applies the config file and any settings
+/// given on the command line on top of its config.
+fn build_world(args: &Args) -> Result<World, String> {
+    let mut world = match (&args.load, args.seed) {
+        (Some(path), _) => {
+            World::load_from_file(path).map_err(|err| format!("{}: {}", path.display(), err))?
+        }
+        (None, Some(seed)) => World::with_seed(seed),
+        (None, None) => World::new(),
+    };
+    if let Some(path) = &args.config_path {
+        world.config =
+            Config::load(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    world
+        .config
+        .set_all(args.settings.iter().map(|(key, value)| (key.as_str(), value.as_str())))
+        .map_err(|err| err.to_string())?;
+    if let Some(path) = &args.save_config {
+        world
+            .config
+            .save(path)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    if args.load.is_none() {
+        world.spawn_random_organisms(world.config.starting_pop);
+    }
+    Ok(world)
+}
+
+/// Writes the PNG frames asked for by `--frames` and the `--record` recording.
+#[cfg(feature = "raster")]
+struct FrameWriter {
+    sprites: Sprites,
+    frames: Vec<u32>,
+    frames_dir: PathBuf,
+    recorder: Option<Recorder>,
+}
+
+#[cfg(feature = "raster")]
+impl FrameWriter {
+    fn new(args: &Args) -> Result<FrameWriter, String> {
+        let sprites = Sprites::load_dir(&args.assets).map_err(|err| err.to_string())?;
+        if !args.frames.is_empty() {
+            std::fs::create_dir_all(&args.frames_dir)
+                .map_err(|err| format!("{}: {}", args.frames_dir.display(), err))?;
+        }
+        let recorder = match &args.record {
+            Some(path) => Some(Recorder::new(path, args.record_every).map_err(|err| err.to_string())?),
+            None => None,
+        };
+        Ok(FrameWriter {
+            sprites,
+            frames: args.frames.clone(),
+            frames_dir: args.frames_dir.clone(),
+            recorder,
+        })
+    }
+
+    fn write(&mut self, world: &mut World, tick: u32) -> Result<(), String> {
+        if self.frames.contains(&tick) {
+            let path = self.frames_dir.join(format!("frame-{:06}.png", tick));
+            world
+                .to_image(&self.sprites)
+                .save_png(&path)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder
+                .capture(world, &self.sprites, tick)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self.recorder {
+            Some(recorder) => recorder.finish().map_err(|err| err.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "raster"))]
+struct FrameWriter;
+
+#[cfg(not(feature = "raster"))]
+impl FrameWriter {
+    fn new(_args: &Args) -> Result<FrameWriter, String> {
+        Err("--frames and --record need the raster feature, build with --features raster".to_string())
+    }
+
+    fn write(&mut self, _world: &mut World, _tick: u32) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn print_summary(world: &World, ticks: u32) {
+    let info = json::parse(&world.info_as_json()).unwrap();
+    println!("Finished after {} ticks", ticks);
+    for (key, value) in info.entries() {
+        if !value.is_object() {
+            println!("  {:<16} {}", key, value);
+        }
+    }
+    let genetics = world.genetics();
+    for (locus, stats) in &genetics.loci {
+        println!(
+            "  {:<16} heterozygosity {:.3} observed, {:.3} expected",
+            locus, stats.observed_heterozygosity, stats.expected_heterozygosity
+        );
+    }
+    for (name, stats) in &genetics.traits {
+        println!(
+            "  {:<16} mean {:.3}, heritability {:.3}",
+            name, stats.mean, stats.heritability
+        );
+    }
+}
+
 fn main() {
-    let mut world = World::new();
-    world.spawn_random_organisms(100);
-    for _ in 0..1000 {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut world = match build_world(&args) {
+        Ok(world) => world,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    };
+    let mut frames = if args.frames.is_empty() && args.record.is_none() {
+        None
+    } else {
+        match FrameWriter::new(&args) {
+            Ok(frames) => Some(frames),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            }
+        }
+    };
+    let mut write_frame = |world: &mut World, tick: u32| {
+        if let Some(frames) = &mut frames {
+            if let Err(err) = frames.write(world, tick) {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            }
+        }
+    };
+
+    let mut events = match &args.events {
+        Some(path) => match File::create(path) {
+            Ok(file) => {
+                world.buffer_events(usize::MAX);
+                Some(BufWriter::new(file))
+            }
+            Err(err) => {
+                eprintln!("error: {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let mut write_events = |world: &mut World| {
+        if let (Some(writer), Some(path)) = (&mut events, &args.events) {
+            if let Err(err) = write_json_lines(writer, &world.drain_events()) {
+                eprintln!("error: {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+    };
+
+    let mut stats = Stats::new(args.stats_every, DEFAULT_CAPACITY.max(args.ticks as usize + 1));
+    let mut ticks = 0;
+    write_frame(&mut world, ticks);
+    stats.record(&world);
+    while ticks < args.ticks {
         world.tick();
+        ticks += 1;
+        write_frame(&mut world, ticks);
+        stats.record(&world);
+        write_events(&mut world);
+        if args.progress != 0 && ticks % args.progress == 0 {
+            println!("tick {:>8}  population {}", ticks, world.creatures.len());
+        }
+        if world.creatures.is_empty() {
+            println!("Population died out at tick {}", ticks);
+            break;
+        }
+    }
+    if let Some(Err(err)) = frames.map(FrameWriter::finish) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+    if let (Some(Err(err)), Some(path)) = (events.map(|mut writer| writer.flush()), &args.events) {
+        eprintln!("error: {}: {}", path.display(), err);
+        process::exit(1);
+    }
+    print_summary(&world, ticks);
+    if let Some(path) = &args.save {
+        if let Err(err) = world.save_to_file(path) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+    if let Some(path) = &args.stats {
+        if let Err(err) = stats.save(path) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+    if let Some(path) = &args.svg {
+        if let Err(err) = std::fs::write(path, world.to_svg(true)) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+    if let Some(path) = &args.graphml {
+        if let Err(err) = std::fs::write(path, world.pedigree().to_graphml(&world.config)) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+    if let Some(path) = &args.newick {
+        if let Err(err) = std::fs::write(path, world.pedigree().to_newick()) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,338 @@
+use std::fmt::Write;
+
+use std::collections::BTreeMap;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::creature::Creature;
+use crate::genome::Genome;
+
+/// What is known about a creature, kept after it has been removed from the
+/// world.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: u32,
+    /// `None` for creatures spawned into the world rather than born.
+    pub parents: Option<[u32; 2]>,
+    pub children: Vec<u32>,
+    pub genome: Genome,
+    pub born: u64,
+    pub died: Option<u64>,
+}
+
+/// The creatures a world has held, keyed by ID.
+///
+/// IDs only ever increase, so a parent always has a lower ID than its
+/// children. To bound memory, `prune` drops the records of the creatures that
+/// died longest ago, and parents missing from the pedigree are treated as
+/// unrelated founders. Relatedness is then only traced back as far as the
+/// records kept.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pedigree {
+    records: BTreeMap<u32, Record>,
+}
+
+impl Pedigree {
+    /// Records a creature spawned or born during `tick`.
+    pub(crate) fn insert(&mut self, creature: &Creature, tick: u64) {
+        // Spawned creatures have parents of 0, which is never an ID.
+        let parents = Some(creature.parents).filter(|&parents| parents != [0, 0]);
+        for parent in parents.into_iter().flatten() {
+            if let Some(record) = self.records.get_mut(&parent) {
+                record.children.push(creature.id);
+            }
+        }
+        self.records.insert(
+            creature.id,
+            Record {
+                id: creature.id,
+                parents,
+                children: Vec::new(),
+                genome: creature.genome,
+                born: tick,
+                died: None,
+            },
+        );
+    }
+
+    /// Records that a creature died during `tick`.
+    pub(crate) fn record_death(&mut self, id: u32, tick: u64) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.died = Some(tick);
+        }
+    }
+
+    /// Drops the records of dead creatures, lowest ID first, until at most
+    /// `capacity` are left or only living creatures remain.
+    pub(crate) fn prune(&mut self, capacity: usize) {
+        let excess = self.records.len().saturating_sub(capacity);
+        if excess == 0 {
+            return;
+        }
+        let dead: Vec<u32> = self
+            .records
+            .values()
+            .filter(|record| record.died.is_some())
+            .map(|record| record.id)
+            .take(excess)
+            .collect();
+        for id in dead {
+            let record = self.records.remove(&id).unwrap();
+            for parent in record.parents.into_iter().flatten() {
+                if let Some(parent) = self.records.get_mut(&parent) {
+                    parent.children.retain(|&child| child != id);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Every record, lowest ID first.
+    pub fn records(&self) -> impl Iterator<Item = &Record> {
+        self.records.values()
+    }
+
+    fn parents(&self, id: u32) -> Option<[u32; 2]> {
+        self.records.get(&id).and_then(|record| record.parents)
+    }
+
+    /// The IDs of every known ancestor of `id`, lowest first.
+    pub fn ancestors(&self, id: u32) -> Vec<u32> {
+        self.walk(id, |id| {
+            self.parents(id)
+                .into_iter()
+                .flatten()
+                .filter(|parent| self.records.contains_key(parent))
+                .collect()
+        })
+    }
+
+    /// The IDs of every known descendant of `id`, lowest first.
+    pub fn descendants(&self, id: u32) -> Vec<u32> {
+        self.walk(id, |id| {
+            self.records
+                .get(&id)
+                .map_or_else(Vec::new, |record| record.children.clone())
+        })
+    }
+
+    fn walk(&self, start: u32, next: impl Fn(u32) -> Vec<u32>) -> Vec<u32> {
+        let mut seen = FxHashSet::default();
+        let mut stack = next(start);
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                stack.extend(next(id));
+            }
+        }
+        let mut found: Vec<u32> = seen.into_iter().collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// The probability that an allele picked at random from `a` and one from
+    /// `b` are identical by descent.
+    pub fn kinship(&self, a: u32, b: u32) -> f64 {
+        let key = |x: u32, y: u32| (x.min(y), x.max(y));
+        let mut known: FxHashMap<(u32, u32), f64> = FxHashMap::default();
+        let mut stack = vec![key(a, b)];
+        // Working from the younger of each pair is always safe, as it can't
+        // be an ancestor of the older one. This is done with an explicit
+        // stack as pedigrees can be thousands of generations deep.
+        while let Some(&(older, younger)) = stack.last() {
+            if known.contains_key(&(older, younger)) {
+                stack.pop();
+                continue;
+            }
+            let needed = match self.parents(younger) {
+                None => {
+                    let value = if older == younger { 0.5 } else { 0.0 };
+                    known.insert((older, younger), value);
+                    stack.pop();
+                    continue;
+                }
+                Some([p, q]) if older == younger => vec![key(p, q)],
+                Some([p, q]) => vec![key(p, older), key(q, older)],
+            };
+            let missing: Vec<_> = needed
+                .iter()
+                .filter(|pair| !known.contains_key(pair))
+                .copied()
+                .collect();
+            if !missing.is_empty() {
+                stack.extend(missing);
+                continue;
+            }
+            let value = if older == younger {
+                (1.0 + known[&needed[0]]) / 2.0
+            } else {
+                (known[&needed[0]] + known[&needed[1]]) / 2.0
+            };
+            known.insert((older, younger), value);
+            stack.pop();
+        }
+        known[&key(a, b)]
+    }
+
+    /// The coefficient of relatedness, twice the kinship. Without inbreeding
+    /// this is 1 for a creature with itself and 0.5 between a parent and child
+    /// or full siblings; inbreeding pushes it higher, up to 2.
+    pub fn relatedness(&self, a: u32, b: u32) -> f64 {
+        2.0 * self.kinship(a, b)
+    }
+
+    /// The whole pedigree as a GraphML document, with a node per creature
+    /// carrying its genotype and phenotype at every locus, as expressed under
+    /// `config`, and an edge to each child from each of its parents still in
+    /// the pedigree.
+    pub fn to_graphml(&self, config: &Config) -> String {
+        let mut xml = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#,
+            "\n",
+        ));
+        for locus in Genome::loci() {
+            writeln!(
+                xml,
+                r#"<key id="{0}_genes" for="node" attr.name="{0}_genes" attr.type="string"/>"#,
+                locus
+            )
+            .unwrap();
+            writeln!(
+                xml,
+                r#"<key id="{0}" for="node" attr.name="{0}" attr.type="string"/>"#,
+                locus
+            )
+            .unwrap();
+        }
+        for (key, target, kind) in GRAPHML_KEYS {
+            writeln!(
+                xml,
+                r#"<key id="{0}" for="{1}" attr.name="{0}" attr.type="{2}"/>"#,
+                key, target, kind
+            )
+            .unwrap();
+        }
+        xml.push_str("<graph id=\"pedigree\" edgedefault=\"directed\">\n");
+        for record in self.records.values() {
+            write!(xml, r#"<node id="n{}">"#, record.id).unwrap();
+            for locus in record.genome.expressed(config) {
+                write!(
+                    xml,
+                    r#"<data key="{0}_genes">{1}</data><data key="{0}">{2}</data>"#,
+                    locus.locus, locus.genotype, locus.phenotype
+                )
+                .unwrap();
+            }
+            write!(xml, r#"<data key="born">{}</data>"#, record.born).unwrap();
+            if let Some(died) = record.died {
+                write!(xml, r#"<data key="died">{}</data>"#, died).unwrap();
+            }
+            xml.push_str("</node>\n");
+        }
+        for record in self.records.values() {
+            for (index, parent) in record.parents.into_iter().flatten().enumerate() {
+                if !self.records.contains_key(&parent) {
+                    continue;
+                }
+                writeln!(
+                    xml,
+                    r#"<edge source="n{}" target="n{}"><data key="first_parent">{}</data></edge>"#,
+                    parent,
+                    record.id,
+                    index == 0
+                )
+                .unwrap();
+            }
+        }
+        xml.push_str("</graph>\n</graphml>\n");
+        xml
+    }
+
+    /// The lineages through each creature's first parent as Newick trees, one
+    /// per line, rooted at the founders. Nodes are labelled by ID, with branch
+    /// lengths in ticks between births.
+    pub fn to_newick(&self) -> String {
+        let first_parent = |record: &Record| {
+            record
+                .parents
+                .map(|[parent, _]| parent)
+                .filter(|parent| self.records.contains_key(parent))
+        };
+        let mut lineage: FxHashMap<u32, Vec<u32>> = FxHashMap::default();
+        let mut roots = Vec::new();
+        for record in self.records.values() {
+            match first_parent(record) {
+                Some(parent) => lineage.entry(parent).or_default().push(record.id),
+                None => roots.push(record.id),
+            }
+        }
+
+        enum Step {
+            Open(u32),
+            Close(u32),
+            Comma,
+        }
+        let mut newick = String::new();
+        let label = |newick: &mut String, id: u32| {
+            let record = &self.records[&id];
+            match first_parent(record) {
+                Some(parent) => {
+                    let length = record.born - self.records[&parent].born;
+                    write!(newick, "{}:{}", id, length).unwrap();
+                }
+                None => write!(newick, "{}", id).unwrap(),
+            }
+        };
+        // Lineages can be thousands of generations deep, so the tree is
+        // walked with an explicit stack rather than recursion.
+        for root in roots {
+            let mut stack = vec![Step::Open(root)];
+            while let Some(step) = stack.pop() {
+                match step {
+                    Step::Open(id) => match lineage.get(&id) {
+                        Some(children) => {
+                            newick.push('(');
+                            stack.push(Step::Close(id));
+                            for (i, &child) in children.iter().enumerate().rev() {
+                                stack.push(Step::Open(child));
+                                if i > 0 {
+                                    stack.push(Step::Comma);
+                                }
+                            }
+                        }
+                        None => label(&mut newick, id),
+                    },
+                    Step::Close(id) => {
+                        newick.push(')');
+                        label(&mut newick, id);
+                    }
+                    Step::Comma => newick.push(','),
+                }
+            }
+            newick.push_str(";\n");
+        }
+        newick
+    }
+}
+
+/// The GraphML attributes of each node and edge besides the genotype and
+/// phenotype at each locus.
+const GRAPHML_KEYS: [(&str, &str, &str); 3] = [
+    ("born", "node", "long"),
+    ("died", "node", "long"),
+    ("first_parent", "edge", "boolean"),
+];
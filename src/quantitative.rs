@@ -0,0 +1,64 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gene::POLYGENE_LOCI;
+use crate::genome::Genome;
+
+/// The most a creature of average turn rate turns in one tick.
+pub const MEAN_TURN: f64 = PI / 32.0;
+
+/// Continuous traits, each set at birth by the additive effects of the loci
+/// behind it plus environmental noise.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Traits {
+    /// Scales how far the creature swims each tick, averaging 1.
+    pub size: f64,
+    /// Scales how sharply the creature turns, averaging 1.
+    pub turn_rate: f64,
+    /// How far away the creature notices the point it swims towards.
+    pub vision: f64,
+}
+
+impl Traits {
+    pub const NAMES: [&'static str; 3] = ["size", "turn_rate", "vision"];
+
+    /// The value of each trait without noise. A creature carrying the
+    /// increasing allele at half its loci gets the trait's mean.
+    pub fn genotypic(genome: &Genome, config: &Config) -> Traits {
+        let value = |increasing: u32, mean: f64| {
+            mean * (1.0 + config.additive_effect * (increasing as f64 - POLYGENE_LOCI as f64))
+        };
+        Traits {
+            size: value(genome.size.increasing(), 1.0),
+            turn_rate: value(genome.turn_rate.increasing(), 1.0),
+            vision: value(genome.vision.increasing(), config.vision_range),
+        }
+    }
+
+    /// The genotypic values plus normally distributed noise, kept from going
+    /// negative. No randomness is drawn when there is no noise.
+    pub fn express(genome: &Genome, config: &Config, rng: &mut impl Rng) -> Traits {
+        let genotypic = Traits::genotypic(genome, config);
+        if config.environmental_noise <= 0.0 {
+            return genotypic;
+        }
+        let mut noise = |mean: f64| {
+            let z: f64 = StandardNormal.sample(rng);
+            mean * config.environmental_noise * z
+        };
+        Traits {
+            size: (genotypic.size + noise(1.0)).max(0.0),
+            turn_rate: (genotypic.turn_rate + noise(1.0)).max(0.0),
+            vision: (genotypic.vision + noise(config.vision_range)).max(0.0),
+        }
+    }
+
+    /// The traits in `NAMES` order.
+    pub fn values(&self) -> [f64; 3] {
+        [self.size, self.turn_rate, self.vision]
+    }
+}
@@ -0,0 +1,136 @@
+use super::{in_patch, parse_colour, Fish, Renderer};
+use crate::gene::Skin;
+use crate::utils::{Bounds, Point};
+
+/// The Unicode braille bit for each dot, indexed by `[row][column]` within a
+/// character cell of two columns and four rows.
+const DOT_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+const DEAD_COLOUR: [u8; 3] = [128, 128, 128];
+
+/// One character of a braille drawing. Colours are red, green and blue.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Cell {
+    pub dots: u8,
+    pub colour: Option<[u8; 3]>,
+    pub highlight: Option<[u8; 3]>,
+}
+
+impl Cell {
+    pub fn char(&self) -> char {
+        char::from_u32(0x2800 + self.dots as u32).unwrap()
+    }
+}
+
+/// Draws onto a grid of braille characters for terminals, each holding 2×4
+/// dots. Fish are drawn as a line of dots along their body, coloured by the
+/// last fish drawn in the cell, and outlines highlight the cell behind a fish.
+/// Debris is left out to keep the fish readable.
+pub struct BrailleRenderer {
+    columns: usize,
+    rows: usize,
+    bounds: Bounds,
+    dot_size: f64,
+    cells: Vec<Cell>,
+}
+
+impl BrailleRenderer {
+    /// Fits a world of size `bounds` into at most `columns` × `rows`
+    /// characters, keeping its proportions.
+    pub fn new(bounds: Bounds, columns: usize, rows: usize) -> BrailleRenderer {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+        let dot_size = (bounds.width / (columns * 2) as f64).max(bounds.height / (rows * 4) as f64);
+        let columns = ((bounds.width / dot_size / 2.0).ceil() as usize).clamp(1, columns);
+        let rows = ((bounds.height / dot_size / 4.0).ceil() as usize).clamp(1, rows);
+        BrailleRenderer {
+            columns,
+            rows,
+            bounds,
+            dot_size,
+            cells: vec![Cell::default(); columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn row(&self, row: usize) -> &[Cell] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    /// The world position at the centre of the character at `(column, row)`.
+    pub fn world_position(&self, column: usize, row: usize) -> Point {
+        Point::new(
+            (column as f64 * 2.0 + 1.0) * self.dot_size,
+            (row as f64 * 4.0 + 2.0) * self.dot_size,
+        )
+    }
+
+    fn cell_mut(&mut self, loc: Point) -> Option<(&mut Cell, u8)> {
+        let mut loc = loc;
+        loc.wrap(self.bounds);
+        let x = (loc.x / self.dot_size) as usize;
+        let y = (loc.y / self.dot_size) as usize;
+        let (column, row) = (x / 2, y / 4);
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+        let bit = DOT_BITS[y % 4][x % 2];
+        Some((&mut self.cells[row * self.columns + column], bit))
+    }
+
+    fn plot(&mut self, loc: Point, colour: [u8; 3]) {
+        if let Some((cell, bit)) = self.cell_mut(loc) {
+            cell.dots |= bit;
+            cell.colour = Some(colour);
+        }
+    }
+}
+
+impl Renderer for BrailleRenderer {
+    fn draw_background(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    fn draw_debris(&mut self, _loc: Point) {}
+
+    fn draw_fish(&mut self, fish: &Fish) {
+        let colour = |along: f64| {
+            if !fish.alive {
+                return DEAD_COLOUR;
+            }
+            match fish.skin {
+                Skin::Patched(_, second) if in_patch(along) => parse_colour(second.hex()),
+                skin => parse_colour(&skin.hex()),
+            }
+        };
+        let length = 37.0 * fish.age.min(50) as f64 / 100.0;
+        let steps = (length / self.dot_size).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let u = step as f64 / steps as f64;
+            let loc = fish.position.translate4(fish.direction, (u - 0.5) * length, self.bounds);
+            self.plot(loc, colour(u));
+        }
+    }
+
+    fn draw_outline(&mut self, colour: &str, loc: Point) {
+        let colour = parse_colour(colour);
+        if let Some((cell, _)) = self.cell_mut(loc) {
+            cell.highlight = Some(colour);
+        }
+    }
+
+    fn draw_circle(&mut self, colour: &str, centre: Point, _r: f64) {
+        self.plot(centre, parse_colour(colour));
+    }
+
+    fn draw_rectangle(&mut self, colour: &str, centre: Point, _w: f64, _h: f64, _rot: f64) {
+        self.plot(centre, parse_colour(colour));
+    }
+}
@@ -0,0 +1,173 @@
+use super::{background_tiles, Fish, Renderer, PATCH_BANDS};
+use crate::gene::{ColourGene, Skin};
+use crate::utils::Point;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::ImageBitmap;
+
+/// Sprite sheets loaded by the page. Anything missing is skipped when drawing.
+#[derive(Default)]
+pub struct Assets {
+    pub fish: Option<ImageBitmap>,
+    pub bg: Option<ImageBitmap>,
+    pub debris: Option<ImageBitmap>,
+}
+
+/// Draws onto an HTML canvas, `resolution` pixels to a world unit.
+pub struct CanvasRenderer<'a> {
+    context: &'a CanvasRenderingContext2d,
+    assets: &'a Assets,
+    resolution: f64,
+}
+
+impl<'a> CanvasRenderer<'a> {
+    pub fn new(
+        context: &'a CanvasRenderingContext2d,
+        assets: &'a Assets,
+        resolution: f64,
+    ) -> CanvasRenderer<'a> {
+        CanvasRenderer {
+            context,
+            assets,
+            resolution,
+        }
+    }
+
+    fn reset_transform(&self) {
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+    }
+}
+
+impl Renderer for CanvasRenderer<'_> {
+    fn draw_background(&mut self) {
+        let Some(bg) = &self.assets.bg else { return };
+        let canvas = self.context.canvas().unwrap();
+        let (tile, columns, rows) = background_tiles(canvas.width(), canvas.height(), self.resolution);
+        for i in 0..columns {
+            for j in 0..rows {
+                self.context
+                    .draw_image_with_image_bitmap_and_dw_and_dh(
+                        bg,
+                        i as f64 * tile,
+                        j as f64 * tile,
+                        tile,
+                        tile,
+                    )
+                    .unwrap();
+            }
+        }
+    }
+
+    fn draw_debris(&mut self, loc: Point) {
+        let Some(image) = &self.assets.debris else { return };
+        let res = self.resolution;
+        self.reset_transform();
+        self.context.translate(loc.x * res, loc.y * res).unwrap();
+        self.context.rotate(loc.y * 10.0).unwrap();
+        self.context
+            .draw_image_with_image_bitmap_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                image,
+                ((loc.x / 0.8 + loc.y * 1.75) as u32 % 27 * 32) as f64,
+                0.0,
+                32.0,
+                64.0,
+                0.0,
+                0.0,
+                12.0 * res,
+                24.0 * res,
+            )
+            .unwrap();
+        self.reset_transform();
+    }
+
+    fn draw_fish(&mut self, fish: &Fish) {
+        let Some(image) = &self.assets.fish else { return };
+        let res = self.resolution;
+        let mut size = res / 100.0 * fish.age.min(50) as f64;
+        if fish.big {
+            size *= 1.5;
+        }
+        self.context
+            .translate(fish.position.x * res, fish.position.y * res)
+            .unwrap();
+        self.context.rotate(fish.direction).unwrap();
+        let draw_cell = |colour: ColourGene| {
+            self.context
+                .draw_image_with_image_bitmap_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    image,
+                    (colour as u8 * 37) as f64,
+                    (fish.frame * 21) as f64,
+                    37.0,
+                    21.0,
+                    -37.0 * size / 2.0,
+                    -21.0 * size / 2.0,
+                    37.0 * size,
+                    21.0 * size,
+                )
+                .unwrap();
+        };
+        // Patched and blended fish are the cell of their first colour with
+        // that of their second drawn over it, in bands or at half opacity.
+        match fish.skin {
+            Skin::Solid(colour) => draw_cell(colour),
+            Skin::Patched(first, second) => {
+                draw_cell(first);
+                self.context.save();
+                self.context.begin_path();
+                let band = 37.0 * size / PATCH_BANDS as f64;
+                for i in (1..PATCH_BANDS).step_by(2) {
+                    self.context.rect(
+                        -37.0 * size / 2.0 + i as f64 * band,
+                        -21.0 * size / 2.0,
+                        band,
+                        21.0 * size,
+                    );
+                }
+                self.context.clip();
+                draw_cell(second);
+                self.context.restore();
+            }
+            Skin::Blended(first, second) => {
+                draw_cell(first);
+                self.context.set_global_alpha(0.5);
+                draw_cell(second);
+                self.context.set_global_alpha(1.0);
+            }
+        }
+        self.reset_transform();
+    }
+
+    fn draw_outline(&mut self, colour: &str, loc: Point) {
+        let res = self.resolution;
+        self.context.set_stroke_style_str(colour);
+        self.context.stroke_rect(
+            (loc.x - 10.0) * res,
+            (loc.y - 10.0) * res,
+            20.0 * res,
+            20.0 * res,
+        );
+        self.context.set_stroke_style_str("black");
+    }
+
+    fn draw_circle(&mut self, colour: &str, centre: Point, r: f64) {
+        let res = self.resolution;
+        self.context.set_fill_style_str(colour);
+        self.context.begin_path();
+        self.context
+            .arc(centre.x * res, centre.y * res, r * res, 0.0, std::f64::consts::TAU)
+            .unwrap();
+        self.context.fill();
+        self.context.stroke();
+    }
+
+    fn draw_rectangle(&mut self, colour: &str, centre: Point, w: f64, h: f64, rot: f64) {
+        let res = self.resolution;
+        self.context.set_fill_style_str(colour);
+        self.context.translate(centre.x * res, centre.y * res).unwrap();
+        self.context.rotate(rot).unwrap();
+        self.context
+            .fill_rect(-w / 2.0 * res, -h / 2.0 * res, w * res, h * res);
+        self.reset_transform();
+    }
+}
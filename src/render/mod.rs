@@ -0,0 +1,164 @@
+use crate::gene::Skin;
+use crate::utils::{Direction, Point};
+use crate::world::World;
+use std::f64::consts::PI;
+
+#[cfg(feature = "web")]
+pub mod canvas;
+pub mod braille;
+#[cfg(feature = "raster")]
+pub mod raster;
+#[cfg(feature = "raster")]
+pub mod record;
+pub mod svg;
+
+/// Where the selected creature is drawn by `World::render_selected`.
+const SELECTED_POSITION: Point = Point { x: 25.0, y: 25.0 };
+
+/// How many bands a patched fish is split into along its length, every other
+/// one showing its second colour.
+pub const PATCH_BANDS: u32 = 6;
+
+/// Whether the point `u` of the way along a `fish.png` cell, from its left
+/// edge, falls in a patch of a patched fish's second colour.
+pub fn in_patch(u: f64) -> bool {
+    (u * PATCH_BANDS as f64) as u32 % 2 == 1
+}
+
+/// The size of a `bg.png` tile in pixels at `resolution`, and how many tiles
+/// across and down cover a `width` by `height` pixel view.
+pub fn background_tiles(width: u32, height: u32, resolution: f64) -> (f64, u32, u32) {
+    let tile = 256.0 * resolution;
+    let count = |pixels: u32| (pixels as f64 / tile).ceil() as u32;
+    (tile, count(width), count(height))
+}
+
+/// A fish to be drawn, in world coordinates. `frame` is the column of the
+/// swimming animation and is always 0 for dead fish.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fish {
+    pub skin: Skin,
+    pub frame: u8,
+    pub position: Point,
+    pub direction: Direction,
+    pub age: u32,
+    pub alive: bool,
+    pub big: bool,
+}
+
+/// A drawing backend. All positions and sizes are in world units; scaling
+/// them to pixels, characters or anything else is up to the implementation.
+/// Colours are CSS colour names.
+pub trait Renderer {
+    /// Covers the whole view, replacing whatever was drawn before.
+    fn draw_background(&mut self);
+
+    fn draw_debris(&mut self, loc: Point);
+
+    fn draw_fish(&mut self, fish: &Fish);
+
+    /// A square around a fish, used to highlight the selection and its family.
+    fn draw_outline(&mut self, colour: &str, loc: Point);
+
+    fn draw_circle(&mut self, colour: &str, centre: Point, r: f64);
+
+    fn draw_rectangle(&mut self, colour: &str, centre: Point, w: f64, h: f64, rot: f64);
+
+    fn draw_eye(&mut self, colour: &str, centre: Point, r: f64) {
+        self.draw_circle("white", centre, r);
+        self.draw_circle(colour, centre, r / 1.3);
+        self.draw_circle("black", centre, r / 4.0);
+    }
+
+    fn draw_dead_eye(&mut self, centre: Point, r: f64, direction: Direction) {
+        self.draw_circle("white", centre, r);
+        self.draw_circle("grey", centre, r / 1.3);
+        self.draw_rectangle("black", centre, r / 1.2, r / 4.0, direction - PI / 4.0);
+        self.draw_rectangle("black", centre, r / 1.2, r / 4.0, direction + PI / 4.0);
+    }
+}
+
+/// Understands `#rrggbb` and the handful of colour names the renderers use,
+/// returning red, green and blue. Anything else is black.
+pub fn parse_colour(colour: &str) -> [u8; 3] {
+    if let Some(hex) = colour.strip_prefix('#') {
+        if let Ok(rgb) = u32::from_str_radix(hex, 16) {
+            if hex.len() == 6 {
+                return [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8];
+            }
+        }
+    }
+    match colour {
+        "white" => [255, 255, 255],
+        "grey" | "gray" => [128, 128, 128],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "blue" => [0, 0, 255],
+        _ => [0, 0, 0],
+    }
+}
+
+impl World {
+    /// Draws the background, debris and every creature, outlining the
+    /// selected creature in red and its relatives in blue and green.
+    pub fn draw(&self, renderer: &mut impl Renderer) {
+        renderer.draw_background();
+        for loc in &self.debris_locs {
+            renderer.draw_debris(*loc);
+        }
+        let selected_id = self.selected.unwrap_or(0);
+        for creature in &self.creatures {
+            if creature.id == selected_id {
+                renderer.draw_outline("red", creature.position)
+            } else if creature.parents.contains(&selected_id) {
+                renderer.draw_outline("blue", creature.position)
+            } else if creature.offspring.contains(&selected_id) {
+                renderer.draw_outline("green", creature.position)
+            }
+            renderer.draw_fish(&Fish {
+                skin: creature.skin(&self.config),
+                frame: self.animation_frame(creature.is_alive),
+                position: creature.position,
+                direction: creature.direction,
+                age: creature.age,
+                alive: creature.is_alive,
+                big: false,
+            });
+        }
+    }
+
+    /// Draws the selected creature on its own, enlarged, for the info panel.
+    pub fn draw_selected(&self, renderer: &mut impl Renderer) {
+        let Some(id) = self.selected else { return };
+        for creature in self.creatures.iter().filter(|creature| creature.id == id) {
+            renderer.draw_fish(&Fish {
+                skin: creature.skin(&self.config),
+                frame: self.animation_frame(creature.is_alive),
+                position: SELECTED_POSITION,
+                direction: creature.direction,
+                age: creature.age,
+                alive: creature.is_alive,
+                big: true,
+            });
+        }
+    }
+
+    /// Draws the world and advances the swimming animation.
+    pub fn render(&mut self, renderer: &mut impl Renderer) {
+        self.draw(renderer);
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+    }
+
+    pub fn render_selected(&mut self, renderer: &mut impl Renderer) {
+        self.draw_selected(renderer);
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+    }
+
+    fn animation_frame(&self, is_alive: bool) -> u8 {
+        if is_alive {
+            (self.frame_counter / 4) % 8
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt;
+use std::path::Path;
+
+use super::{background_tiles, in_patch, parse_colour, Fish, Renderer};
+use crate::gene::{ColourGene, Skin};
+use crate::utils::Point;
+use crate::world::World;
+
+/// Matches the blue the web page clears the canvas to before drawing.
+const CLEAR_COLOUR: [u8; 4] = [0, 0, 255, 255];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RasterError {
+    Decode(String),
+    Encode(String),
+    Io(String),
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RasterError::Decode(err) => write!(f, "couldn't decode image: {}", err),
+            RasterError::Encode(err) => write!(f, "couldn't encode image: {}", err),
+            RasterError::Io(err) => write!(f, "couldn't access image file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RasterError {}
+
+impl RasterError {
+    /// Names the file an I/O error happened on.
+    pub(crate) fn in_file(self, path: &Path) -> RasterError {
+        match self {
+            RasterError::Io(err) => RasterError::Io(format!("{}: {}", path.display(), err)),
+            err => err,
+        }
+    }
+}
+
+/// An 8-bit RGBA image, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, colour: [u8; 4]) -> Image {
+        Image {
+            width,
+            height,
+            pixels: colour.repeat((width * height) as usize),
+        }
+    }
+
+    /// Decodes any PNG, converting it to 8-bit RGBA.
+    pub fn decode_png(bytes: &[u8]) -> Result<Image, RasterError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|err| RasterError::Decode(err.to_string()))?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| RasterError::Decode(err.to_string()))?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(RasterError::Decode("unexpanded palette".to_string()))
+            }
+        };
+        Ok(Image {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    pub fn encode_png(&self) -> Result<Vec<u8>, RasterError> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|err| RasterError::Encode(err.to_string()))?;
+        Ok(bytes)
+    }
+
+    pub fn load_png(path: &Path) -> Result<Image, RasterError> {
+        let bytes = std::fs::read(path).map_err(|err| RasterError::Io(err.to_string()))?;
+        Image::decode_png(&bytes)
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), RasterError> {
+        std::fs::write(path, self.encode_png()?).map_err(|err| RasterError::Io(err.to_string()))
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Draws `colour` over the pixel at `(x, y)`, respecting its alpha.
+    fn blend(&mut self, x: u32, y: u32, colour: [u8; 4]) {
+        let i = ((y * self.width + x) * 4) as usize;
+        let alpha = colour[3] as u32;
+        for (under, over) in self.pixels[i..i + 3].iter_mut().zip(colour) {
+            *under = ((over as u32 * alpha + *under as u32 * (255 - alpha)) / 255) as u8;
+        }
+        let under = self.pixels[i + 3] as u32;
+        self.pixels[i + 3] = (alpha + under * (255 - alpha) / 255) as u8;
+    }
+}
+
+/// The sprite sheets from `web/assets`. Anything missing is skipped when
+/// drawing, as on the web page.
+#[derive(Debug, Clone, Default)]
+pub struct Sprites {
+    pub fish: Option<Image>,
+    pub bg: Option<Image>,
+    pub debris: Option<Image>,
+}
+
+impl Sprites {
+    /// Loads `fish.png`, `bg.png` and `debris.png` from `dir`.
+    pub fn load_dir(dir: &Path) -> Result<Sprites, RasterError> {
+        let load = |name: &str| {
+            let path = dir.join(name);
+            Image::load_png(&path).map_err(|err| err.in_file(&path))
+        };
+        Ok(Sprites {
+            fish: Some(load("fish.png")?),
+            bg: Some(load("bg.png")?),
+            debris: Some(load("debris.png")?),
+        })
+    }
+}
+
+/// Draws into an in-memory image, `resolution` pixels to a world unit, with
+/// the same sprite layout and transforms as the canvas renderer.
+pub struct RasterRenderer<'a> {
+    sprites: &'a Sprites,
+    resolution: f64,
+    image: Image,
+}
+
+impl<'a> RasterRenderer<'a> {
+    pub fn new(sprites: &'a Sprites, width: u32, height: u32, resolution: f64) -> RasterRenderer<'a> {
+        RasterRenderer {
+            sprites,
+            resolution,
+            image: Image::new(width, height, CLEAR_COLOUR),
+        }
+    }
+
+    pub fn into_image(self) -> Image {
+        self.image
+    }
+
+    /// Fills the rectangle `(x, y, w, h)` given in pixels, after rotating it
+    /// by `rot` and moving it to `origin`, as the canvas `translate`, `rotate`
+    /// and `drawImage` calls do. `shade` is given the position within the
+    /// rectangle, from 0 to 1 on each axis, and may leave the pixel alone.
+    fn fill_quad(
+        &mut self,
+        origin: (f64, f64),
+        rot: f64,
+        (x, y, w, h): (f64, f64, f64, f64),
+        shade: impl Fn(f64, f64) -> Option<[u8; 4]>,
+    ) {
+        if w <= 0.0 || h <= 0.0 {
+            return;
+        }
+        let (sin, cos) = rot.sin_cos();
+        let corners = [(x, y), (x + w, y), (x, y + h), (x + w, y + h)]
+            .map(|(cx, cy)| (origin.0 + cx * cos - cy * sin, origin.1 + cx * sin + cy * cos));
+        let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let max_y = corners.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+        let columns = min_x.floor().max(0.0) as u32..(max_x.ceil().max(0.0) as u32).min(self.image.width);
+        let rows = min_y.floor().max(0.0) as u32..(max_y.ceil().max(0.0) as u32).min(self.image.height);
+        for py in rows {
+            for px in columns.clone() {
+                let dx = px as f64 + 0.5 - origin.0;
+                let dy = py as f64 + 0.5 - origin.1;
+                let u = (dx * cos + dy * sin - x) / w;
+                let v = (-dx * sin + dy * cos - y) / h;
+                if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                    continue;
+                }
+                if let Some(colour) = shade(u, v) {
+                    self.image.blend(px, py, colour);
+                }
+            }
+        }
+    }
+
+    /// Draws the `(sx, sy, sw, sh)` cell of `sprite` into the given
+    /// rectangle, sampling the nearest source pixel.
+    fn blit(
+        &mut self,
+        sprite: &Image,
+        cell: (u32, u32, u32, u32),
+        origin: (f64, f64),
+        rot: f64,
+        rect: (f64, f64, f64, f64),
+    ) {
+        self.blit_faded(sprite, cell, origin, rot, rect, |_| 255);
+    }
+
+    /// Like `blit`, with the sprite's alpha scaled by `opacity`, from 0 to
+    /// 255, given how far across the cell each pixel is.
+    fn blit_faded(
+        &mut self,
+        sprite: &Image,
+        (sx, sy, sw, sh): (u32, u32, u32, u32),
+        origin: (f64, f64),
+        rot: f64,
+        rect: (f64, f64, f64, f64),
+        opacity: impl Fn(f64) -> u32,
+    ) {
+        if sx + sw > sprite.width || sy + sh > sprite.height {
+            return;
+        }
+        self.fill_quad(origin, rot, rect, |u, v| {
+            let opacity = opacity(u);
+            if opacity == 0 {
+                return None;
+            }
+            let mut pixel = sprite.pixel(sx + (u * sw as f64) as u32, sy + (v * sh as f64) as u32);
+            pixel[3] = (pixel[3] as u32 * opacity / 255) as u8;
+            Some(pixel)
+        });
+    }
+}
+
+impl Renderer for RasterRenderer<'_> {
+    fn draw_background(&mut self) {
+        let Some(bg) = &self.sprites.bg else { return };
+        let (tile, columns, rows) =
+            background_tiles(self.image.width, self.image.height, self.resolution);
+        for i in 0..columns {
+            for j in 0..rows {
+                self.blit(
+                    bg,
+                    (0, 0, bg.width, bg.height),
+                    (i as f64 * tile, j as f64 * tile),
+                    0.0,
+                    (0.0, 0.0, tile, tile),
+                );
+            }
+        }
+    }
+
+    fn draw_debris(&mut self, loc: Point) {
+        let Some(image) = &self.sprites.debris else { return };
+        let res = self.resolution;
+        self.blit(
+            image,
+            ((loc.x / 0.8 + loc.y * 1.75) as u32 % 27 * 32, 0, 32, 64),
+            (loc.x * res, loc.y * res),
+            loc.y * 10.0,
+            (0.0, 0.0, 12.0 * res, 24.0 * res),
+        );
+    }
+
+    fn draw_fish(&mut self, fish: &Fish) {
+        let Some(image) = &self.sprites.fish else { return };
+        let res = self.resolution;
+        let mut size = res / 100.0 * fish.age.min(50) as f64;
+        if fish.big {
+            size *= 1.5;
+        }
+        let cell = |colour: ColourGene| (colour as u32 * 37, fish.frame as u32 * 21, 37, 21);
+        let origin = (fish.position.x * res, fish.position.y * res);
+        let rect = (-37.0 * size / 2.0, -21.0 * size / 2.0, 37.0 * size, 21.0 * size);
+        // Patched and blended fish are the cell of their first colour with
+        // that of their second drawn over it, in bands or at half opacity.
+        let direction = fish.direction;
+        match fish.skin {
+            Skin::Solid(colour) => self.blit(image, cell(colour), origin, direction, rect),
+            Skin::Patched(first, second) => {
+                self.blit(image, cell(first), origin, direction, rect);
+                self.blit_faded(image, cell(second), origin, direction, rect, |u| {
+                    in_patch(u) as u32 * 255
+                });
+            }
+            Skin::Blended(first, second) => {
+                self.blit(image, cell(first), origin, direction, rect);
+                self.blit_faded(image, cell(second), origin, direction, rect, |_| 128);
+            }
+        }
+    }
+
+    fn draw_outline(&mut self, colour: &str, loc: Point) {
+        let res = self.resolution;
+        let colour = opaque(parse_colour(colour));
+        let (x, y, side) = ((loc.x - 10.0) * res, (loc.y - 10.0) * res, 20.0 * res);
+        // One pixel wide lines centred on the edges, like `strokeRect`.
+        for edge in [
+            (x - 0.5, y - 0.5, side + 1.0, 1.0),
+            (x - 0.5, y + side - 0.5, side + 1.0, 1.0),
+            (x - 0.5, y - 0.5, 1.0, side + 1.0),
+            (x + side - 0.5, y - 0.5, 1.0, side + 1.0),
+        ] {
+            self.fill_quad((0.0, 0.0), 0.0, edge, |_, _| Some(colour));
+        }
+    }
+
+    fn draw_circle(&mut self, colour: &str, centre: Point, r: f64) {
+        let res = self.resolution;
+        let fill = opaque(parse_colour(colour));
+        let (cx, cy, r) = (centre.x * res, centre.y * res, r * res);
+        let outer = r + 0.5;
+        self.fill_quad((cx, cy), 0.0, (-outer, -outer, outer * 2.0, outer * 2.0), |u, v| {
+            let distance = ((u - 0.5).hypot(v - 0.5)) * outer * 2.0;
+            if distance >= outer {
+                None
+            } else if distance >= r - 0.5 {
+                Some([0, 0, 0, 255])
+            } else {
+                Some(fill)
+            }
+        });
+    }
+
+    fn draw_rectangle(&mut self, colour: &str, centre: Point, w: f64, h: f64, rot: f64) {
+        let res = self.resolution;
+        let colour = opaque(parse_colour(colour));
+        self.fill_quad(
+            (centre.x * res, centre.y * res),
+            rot,
+            (-w / 2.0 * res, -h / 2.0 * res, w * res, h * res),
+            |_, _| Some(colour),
+        );
+    }
+}
+
+fn opaque([r, g, b]: [u8; 3]) -> [u8; 4] {
+    [r, g, b, 255]
+}
+
+impl World {
+    /// Draws the world at its configured resolution, without advancing the
+    /// animation.
+    pub fn to_image(&self, sprites: &Sprites) -> Image {
+        let res = self.config.resolution;
+        let mut renderer = RasterRenderer::new(
+            sprites,
+            (self.config.width * res).round() as u32,
+            (self.config.height * res).round() as u32,
+            res,
+        );
+        self.draw(&mut renderer);
+        renderer.into_image()
+    }
+}
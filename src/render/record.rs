@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use super::raster::{RasterError, RasterRenderer, Sprites};
+use crate::world::World;
+
+/// How long each GIF frame is shown for, in hundredths of a second.
+const GIF_DELAY: u16 = 4;
+
+/// Trades GIF colour quality for speed, from 1 (best) to 30 (fastest).
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+enum Sink {
+    Gif {
+        path: PathBuf,
+        encoder: Option<gif::Encoder<BufWriter<File>>>,
+    },
+    Png {
+        dir: PathBuf,
+    },
+}
+
+/// Captures every `every`th tick of a run, either into an animated GIF or as
+/// numbered PNGs in a directory. Each captured frame advances the world's
+/// swimming animation, as rendering a frame on the web page does.
+pub struct Recorder {
+    every: u32,
+    sink: Sink,
+    frames: u32,
+}
+
+impl Recorder {
+    /// Records into `path`, which is a GIF if it ends in `.gif` and otherwise
+    /// a directory, created if needed, of `frame-<tick>.png` files.
+    pub fn new(path: &Path, every: u32) -> Result<Recorder, RasterError> {
+        let sink = if is_gif(path) {
+            Sink::Gif {
+                path: path.to_path_buf(),
+                encoder: None,
+            }
+        } else {
+            std::fs::create_dir_all(path)
+                .map_err(|err| RasterError::Io(err.to_string()).in_file(path))?;
+            Sink::Png {
+                dir: path.to_path_buf(),
+            }
+        };
+        Ok(Recorder {
+            every: every.max(1),
+            sink,
+            frames: 0,
+        })
+    }
+
+    /// The number of frames captured so far.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Captures a frame if `tick` is one of the ticks being recorded.
+    pub fn capture(
+        &mut self,
+        world: &mut World,
+        sprites: &Sprites,
+        tick: u32,
+    ) -> Result<(), RasterError> {
+        if !tick.is_multiple_of(self.every) {
+            return Ok(());
+        }
+        let res = world.config.resolution;
+        let mut renderer = RasterRenderer::new(
+            sprites,
+            (world.config.width * res).round() as u32,
+            (world.config.height * res).round() as u32,
+            res,
+        );
+        world.render(&mut renderer);
+        let mut image = renderer.into_image();
+        match &mut self.sink {
+            Sink::Png { dir } => {
+                let path = dir.join(format!("frame-{:06}.png", tick));
+                image.save_png(&path).map_err(|err| err.in_file(&path))?;
+            }
+            Sink::Gif { path, encoder } => {
+                let (width, height) = match (u16::try_from(image.width), u16::try_from(image.height)) {
+                    (Ok(width), Ok(height)) => (width, height),
+                    _ => return Err(RasterError::Encode("frame too large for a GIF".to_string())),
+                };
+                if encoder.is_none() {
+                    let file = File::create(&*path)
+                        .map_err(|err| RasterError::Io(err.to_string()).in_file(path))?;
+                    let mut gif = gif::Encoder::new(BufWriter::new(file), width, height, &[])
+                        .map_err(|err| RasterError::Encode(err.to_string()))?;
+                    gif.set_repeat(gif::Repeat::Infinite)
+                        .map_err(|err| RasterError::Encode(err.to_string()))?;
+                    *encoder = Some(gif);
+                }
+                let mut frame =
+                    gif::Frame::from_rgba_speed(width, height, &mut image.pixels, GIF_QUANTIZE_SPEED);
+                frame.delay = GIF_DELAY;
+                encoder
+                    .as_mut()
+                    .unwrap()
+                    .write_frame(&frame)
+                    .map_err(|err| RasterError::Encode(err.to_string()))?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finishes the GIF, reporting any error writing its last bytes.
+    pub fn finish(self) -> Result<(), RasterError> {
+        if let Sink::Gif {
+            path,
+            encoder: Some(encoder),
+        } = self.sink
+        {
+            encoder
+                .into_inner()
+                .and_then(|mut writer| std::io::Write::flush(&mut writer))
+                .map_err(|err| RasterError::Io(err.to_string()).in_file(&path))?;
+        }
+        Ok(())
+    }
+}
+
+fn is_gif(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gif")
+}
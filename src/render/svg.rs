@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use super::{Fish, Renderer, PATCH_BANDS};
+use crate::gene::{ColourGene, Skin};
+use crate::utils::Point;
+use crate::world::World;
+
+const BACKGROUND: &str = "#d8c690";
+const DEBRIS: &str = "#8a6a4a";
+
+/// A fish body and tail drawn in the 37×21 space of a `fish.png` cell,
+/// centred on the origin and facing along the x axis.
+const FISH_SHAPE: &str =
+    r#"<ellipse cx="4" rx="13" ry="6"/><polygon points="-8,0 -18,-7 -18,7"/>"#;
+
+/// Builds a standalone SVG document. Everything is drawn in world units, with
+/// the document's `viewBox` covering the world, so it scales to any size.
+pub struct SvgRenderer {
+    width: f64,
+    height: f64,
+    body: String,
+    /// Whether a patched fish has been drawn, needing the `patches` clip path.
+    patched: bool,
+}
+
+impl SvgRenderer {
+    pub fn new(width: f64, height: f64) -> SvgRenderer {
+        SvgRenderer {
+            width,
+            height,
+            body: String::new(),
+            patched: false,
+        }
+    }
+
+    /// Draws a key of how many creatures there are of each colour in the top
+    /// left corner.
+    pub fn draw_legend(&mut self, counts: &[(Skin, usize)]) {
+        let height = counts.len() as f64 * 6.0 + 2.0;
+        let width = if counts.len() > ColourGene::ALL.len() { 56 } else { 36 };
+        write!(
+            self.body,
+            r#"<g font-family="sans-serif" font-size="4"><rect x="2" y="2" width="{}" height="{}" fill="white" fill-opacity="0.8"/>"#,
+            width,
+            height
+        )
+        .unwrap();
+        for (row, (colour, count)) in counts.iter().enumerate() {
+            let y = row as f64 * 6.0 + 4.0;
+            write!(
+                self.body,
+                r#"<rect x="4" y="{}" width="4" height="4" fill="{}"/><text x="10" y="{}">{} {}</text>"#,
+                y,
+                colour.hex(),
+                y + 3.5,
+                colour,
+                count
+            )
+            .unwrap();
+        }
+        self.body.push_str("</g>");
+    }
+
+    pub fn finish(self) -> String {
+        let mut defs = String::new();
+        if self.patched {
+            defs.push_str(r#"<defs><clipPath id="patches">"#);
+            let band = 37.0 / PATCH_BANDS as f64;
+            for i in (1..PATCH_BANDS).step_by(2) {
+                write!(
+                    defs,
+                    r#"<rect x="{:.2}" y="-10.5" width="{:.2}" height="21"/>"#,
+                    i as f64 * band - 18.5,
+                    band
+                )
+                .unwrap();
+            }
+            defs.push_str("</clipPath></defs>");
+        }
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}">{}{}</svg>"#,
+            defs,
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn draw_background(&mut self) {
+        self.body.clear();
+        write!(
+            self.body,
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            self.width, self.height, BACKGROUND
+        )
+        .unwrap();
+    }
+
+    fn draw_debris(&mut self, loc: Point) {
+        write!(
+            self.body,
+            r#"<rect width="12" height="24" rx="4" fill="{}" fill-opacity="0.7" transform="translate({:.2} {:.2}) rotate({:.1})"/>"#,
+            DEBRIS,
+            loc.x,
+            loc.y,
+            degrees(loc.y * 10.0)
+        )
+        .unwrap();
+    }
+
+    fn draw_fish(&mut self, fish: &Fish) {
+        let mut scale = fish.age.min(50) as f64 / 100.0;
+        if fish.big {
+            scale *= 1.5;
+        }
+        // Patched fish are drawn twice, the second time in their second
+        // colour and clipped to the patches.
+        let patches = match fish.skin {
+            Skin::Patched(_, second) => {
+                self.patched = true;
+                format!(r#"<g fill="{}" clip-path="url(#patches)">{}</g>"#, second.hex(), FISH_SHAPE)
+            }
+            _ => String::new(),
+        };
+        write!(
+            self.body,
+            r#"<g fill="{}"{} transform="translate({:.2} {:.2}) rotate({:.1}) scale({:.2})">{}{}</g>"#,
+            fish.skin.hex(),
+            if fish.alive { "" } else { r#" opacity="0.5""# },
+            fish.position.x,
+            fish.position.y,
+            degrees(fish.direction),
+            scale,
+            FISH_SHAPE,
+            patches
+        )
+        .unwrap();
+    }
+
+    fn draw_outline(&mut self, colour: &str, loc: Point) {
+        write!(
+            self.body,
+            r#"<rect x="{:.2}" y="{:.2}" width="20" height="20" fill="none" stroke="{}" stroke-width="0.5"/>"#,
+            loc.x - 10.0,
+            loc.y - 10.0,
+            colour
+        )
+        .unwrap();
+    }
+
+    fn draw_circle(&mut self, colour: &str, centre: Point, r: f64) {
+        write!(
+            self.body,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}" stroke="black" stroke-width="0.25"/>"#,
+            centre.x, centre.y, r, colour
+        )
+        .unwrap();
+    }
+
+    fn draw_rectangle(&mut self, colour: &str, centre: Point, w: f64, h: f64, rot: f64) {
+        write!(
+            self.body,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" transform="translate({:.2} {:.2}) rotate({:.1})"/>"#,
+            -w / 2.0,
+            -h / 2.0,
+            w,
+            h,
+            colour,
+            centre.x,
+            centre.y,
+            degrees(rot)
+        )
+        .unwrap();
+    }
+}
+
+/// Converts radians to degrees between 0 and 360, as SVG transforms expect.
+fn degrees(radians: f64) -> f64 {
+    radians.to_degrees().rem_euclid(360.0)
+}
+
+impl World {
+    /// Draws the world as a standalone SVG document, optionally with a legend
+    /// of the colour counts.
+    pub fn to_svg(&self, legend: bool) -> String {
+        let mut svg = SvgRenderer::new(self.config.width, self.config.height);
+        self.draw(&mut svg);
+        if legend {
+            svg.draw_legend(&self.colour_counts());
+        }
+        svg.finish()
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigError;
+use crate::world::World;
+
+/// Bumped whenever `World` or its contents change what a snapshot holds.
+/// Snapshots of any other version are rejected rather than filled in with
+/// defaults, so no field of `World` has a serde default.
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// The snapshot was written by a different, incompatible version.
+    UnsupportedVersion(u32),
+    Format(String),
+    Config(ConfigError),
+    Io(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {} is not supported (expected {})",
+                version, SNAPSHOT_VERSION
+            ),
+            SnapshotError::Format(err) => write!(f, "invalid snapshot: {}", err),
+            SnapshotError::Config(err) => write!(f, "invalid snapshot config: {}", err),
+            SnapshotError::Io(err) => write!(f, "couldn't access snapshot file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    world: &'a World,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    world: World,
+}
+
+impl World {
+    /// Serializes the whole simulation, including the random number generator
+    /// state, so a loaded world carries on exactly where this one left off.
+    /// Image assets are not included and must be loaded again.
+    pub fn save(&self) -> String {
+        serde_json::to_string(&SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            world: self,
+        })
+        .unwrap()
+    }
+
+    pub fn load(json: &str) -> Result<World, SnapshotError> {
+        let header: Header =
+            serde_json::from_str(json).map_err(|err| SnapshotError::Format(err.to_string()))?;
+        if header.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header.version));
+        }
+        let snapshot: Snapshot =
+            serde_json::from_str(json).map_err(|err| SnapshotError::Format(err.to_string()))?;
+        snapshot
+            .world
+            .config
+            .validate()
+            .map_err(SnapshotError::Config)?;
+        let mut world = snapshot.world;
+        world.rebuild_grid();
+        Ok(world)
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> Result<(), SnapshotError> {
+        std::fs::write(path, self.save()).map_err(|err| SnapshotError::Io(err.to_string()))
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<World, SnapshotError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|err| SnapshotError::Io(err.to_string()))?;
+        World::load(&json)
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gene::Locus;
+use crate::genome::{Genome, LocusVisitor};
+use crate::quantitative::Traits;
+use crate::world::World;
+
+/// How many samples are kept by default before the oldest are dropped.
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// The state of the living population at one tick. Corpses are left out of
+/// every figure. Phenotype counts are in the order of the `Stats` phenotypes
+/// at the time, and allele frequencies in `allele_names` order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub tick: u64,
+    pub population: usize,
+    /// Births and deaths since the previous sample.
+    pub births: u64,
+    pub deaths: u64,
+    pub mean_age: f64,
+    /// The mean of each quantitative trait, in `Traits::NAMES` order.
+    pub mean_traits: Vec<f64>,
+    pub phenotypes: Vec<usize>,
+    pub alleles: Vec<f64>,
+}
+
+impl Sample {
+    fn new(world: &World, phenotypes: &[String], births: u64, deaths: u64) -> Sample {
+        /// Adds up the phenotypes and alleles of every living creature, one
+        /// locus at a time.
+        struct Counts<'a> {
+            living: &'a [&'a Genome],
+            config: &'a Config,
+            names: &'a [String],
+            phenotypes: Vec<usize>,
+            alleles: Vec<f64>,
+        }
+
+        impl LocusVisitor for Counts<'_> {
+            fn visit<L: Locus>(&mut self, alleles: fn(&Genome) -> [L; 2]) {
+                let phenotypes = L::phenotypes(self.config);
+                let columns: Vec<_> = phenotypes
+                    .iter()
+                    .map(|&phenotype| {
+                        let name = phenotype_name::<L>(phenotype);
+                        self.names.iter().position(|other| *other == name)
+                    })
+                    .collect();
+                let start = self.alleles.len();
+                self.alleles.resize(start + L::ALLELES.len(), 0.0);
+                for genome in self.living {
+                    let [first, second] = alleles(genome);
+                    let phenotype = L::express(first, second, self.config);
+                    let kind = phenotypes.iter().position(|&other| other == phenotype);
+                    if let Some(column) = kind.and_then(|kind| columns[kind]) {
+                        self.phenotypes[column] += 1;
+                    }
+                    for allele in [first, second] {
+                        self.alleles[start + allele.index()] += 1.0;
+                    }
+                }
+            }
+        }
+
+        let living: Vec<_> = world
+            .creatures
+            .iter()
+            .filter(|creature| creature.is_alive)
+            .collect();
+        let mut sample = Sample {
+            tick: world.ticks(),
+            population: living.len(),
+            births,
+            deaths,
+            mean_age: 0.0,
+            mean_traits: vec![0.0; Traits::NAMES.len()],
+            phenotypes: Vec::new(),
+            alleles: Vec::new(),
+        };
+        let mut total_age = 0;
+        for creature in &living {
+            total_age += creature.age as u64;
+            for (total, value) in sample.mean_traits.iter_mut().zip(creature.traits.values()) {
+                *total += value;
+            }
+        }
+        let genomes: Vec<_> = living.iter().map(|creature| &creature.genome).collect();
+        let mut counts = Counts {
+            living: &genomes,
+            config: &world.config,
+            names: phenotypes,
+            phenotypes: vec![0; phenotypes.len()],
+            alleles: Vec::new(),
+        };
+        Genome::visit(&mut counts);
+        sample.phenotypes = counts.phenotypes;
+        sample.alleles = counts.alleles;
+        if sample.population > 0 {
+            let alleles = sample.population as f64 * 2.0;
+            sample.mean_age = total_age as f64 / sample.population as f64;
+            for mean in &mut sample.mean_traits {
+                *mean /= sample.population as f64;
+            }
+            for frequency in &mut sample.alleles {
+                *frequency /= alleles;
+            }
+        }
+        sample
+    }
+}
+
+/// The column name of a phenotype, e.g. `colour_orange` or `speed_fast`.
+fn phenotype_name<L: Locus>(phenotype: L::Phenotype) -> String {
+    format!("{}_{}", L::NAME, L::phenotype_key(phenotype))
+}
+
+/// The name of every phenotype of every locus under `config`, in `visit`
+/// order.
+pub fn phenotype_names(config: &Config) -> Vec<String> {
+    struct Names<'a>(&'a Config, Vec<String>);
+
+    impl LocusVisitor for Names<'_> {
+        fn visit<L: Locus>(&mut self, _: fn(&Genome) -> [L; 2]) {
+            for phenotype in L::phenotypes(self.0) {
+                let name = phenotype_name::<L>(phenotype);
+                if !self.1.contains(&name) {
+                    self.1.push(name);
+                }
+            }
+        }
+    }
+
+    let mut names = Names(config, Vec::new());
+    Genome::visit(&mut names);
+    names.1
+}
+
+/// The name of every allele of every locus, in `Sample::alleles` order.
+pub fn allele_names() -> Vec<String> {
+    struct Names(Vec<String>);
+
+    impl LocusVisitor for Names {
+        fn visit<L: Locus>(&mut self, _: fn(&Genome) -> [L; 2]) {
+            self.0.extend(L::ALLELES.iter().map(|allele| allele.key()));
+        }
+    }
+
+    let mut names = Names(Vec::new());
+    Genome::visit(&mut names);
+    names.0
+}
+
+/// Records a `Sample` of a world every `every` ticks, keeping the most recent
+/// `capacity` of them.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    every: u32,
+    capacity: usize,
+    samples: VecDeque<Sample>,
+    /// Every phenotype possible under the configs in use so far, by
+    /// `phenotype_names`. A config allowing new phenotypes, such as another
+    /// colour dominance model, adds them to the end, so earlier samples count
+    /// a prefix.
+    phenotypes: Vec<String>,
+    /// The world's birth and death totals at the previous sample.
+    last_totals: Option<(u64, u64)>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats::new(1, DEFAULT_CAPACITY)
+    }
+}
+
+impl Stats {
+    pub fn new(every: u32, capacity: usize) -> Stats {
+        Stats {
+            every: every.max(1),
+            capacity: capacity.max(1),
+            samples: VecDeque::new(),
+            phenotypes: Vec::new(),
+            last_totals: None,
+        }
+    }
+
+    /// Takes a sample if the world is on one of the ticks being recorded.
+    /// Call after every tick, and once before the first to include the start.
+    pub fn record(&mut self, world: &World) {
+        if !world.ticks().is_multiple_of(self.every as u64)
+            || self.samples.back().is_some_and(|last| last.tick == world.ticks())
+        {
+            return;
+        }
+        let totals = (world.births(), world.deaths());
+        let (births, deaths) = self.last_totals.unwrap_or(totals);
+        self.last_totals = Some(totals);
+        for name in phenotype_names(&world.config) {
+            if !self.phenotypes.contains(&name) {
+                self.phenotypes.push(name);
+            }
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample::new(
+            world,
+            &self.phenotypes,
+            totals.0 - births,
+            totals.1 - deaths,
+        ));
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+        self.samples.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.phenotypes.clear();
+        self.last_totals = None;
+    }
+
+    /// One row per sample, with a header naming every column.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("tick,population,births,deaths,mean_age");
+        for phenotype in &self.phenotypes {
+            write!(csv, ",{}", phenotype).unwrap();
+        }
+        for allele in allele_names() {
+            write!(csv, ",allele_{}", allele).unwrap();
+        }
+        for name in Traits::NAMES {
+            write!(csv, ",mean_{}", name).unwrap();
+        }
+        csv.push('\n');
+        for sample in &self.samples {
+            write!(
+                csv,
+                "{},{},{},{},{:.2}",
+                sample.tick, sample.population, sample.births, sample.deaths, sample.mean_age
+            )
+            .unwrap();
+            let missing = self.phenotypes.len() - sample.phenotypes.len();
+            for count in sample.phenotypes.iter().chain(std::iter::repeat_n(&0, missing)) {
+                write!(csv, ",{}", count).unwrap();
+            }
+            for frequency in &sample.alleles {
+                write!(csv, ",{:.4}", frequency).unwrap();
+            }
+            for mean in &sample.mean_traits {
+                write!(csv, ",{:.4}", mean).unwrap();
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// The samples along with the names of the phenotypes, alleles and traits
+    /// their arrays are indexed by.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "every": self.every,
+            "phenotypes": self.phenotypes,
+            "alleles": allele_names(),
+            "traits": Traits::NAMES,
+            "samples": self.samples,
+        })
+        .to_string()
+    }
+
+    /// Writes CSV if `path` ends in `.csv`, and JSON otherwise.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let contents = if path.extension().is_some_and(|ext| ext == "csv") {
+            self.to_csv()
+        } else {
+            self.to_json()
+        };
+        std::fs::write(path, contents)
+    }
+}
@@ -1,4 +1,5 @@
-use super::{HEIGHT, WIDTH};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 pub type Direction = f64;
 
@@ -8,32 +9,29 @@ macro_rules! point {
     };
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
-pub fn world_height() -> f64 {
-    unsafe { HEIGHT.clone() }
-}
-
-pub fn world_width() -> f64 {
-    unsafe { WIDTH.clone() }
+/// The size of the toroidal world that points wrap around.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub width: f64,
+    pub height: f64,
 }
 
 impl Point {
     pub fn new(x: f64, y: f64) -> Self {
-        let mut point = Point { x, y };
-        point.wrap();
-        point
+        Point { x, y }
     }
 
-    pub fn new_random() -> Point {
-        let mut x: f64 = rand::random::<f64>();
-        let mut y: f64 = rand::random::<f64>();
-        x *= world_width();
-        y *= world_height();
+    pub fn new_random(rng: &mut impl Rng, bounds: Bounds) -> Point {
+        let mut x: f64 = rng.gen::<f64>();
+        let mut y: f64 = rng.gen::<f64>();
+        x *= bounds.width;
+        y *= bounds.height;
         point!(x, y)
     }
 
@@ -50,25 +48,52 @@ impl Point {
         }
     }
 
-    pub fn translate(&mut self, dx: f64, dy: f64) {
+    /// The shortest `(dx, dy)` from this point to `other`, which may cross
+    /// the edges of the world.
+    pub fn torus_displacement(&self, other: &Point, bounds: Bounds) -> (f64, f64) {
+        let dx = other.x - self.x;
+        let dy = other.y - self.y;
+        (
+            dx - bounds.width * (dx / bounds.width).round(),
+            dy - bounds.height * (dy / bounds.height).round(),
+        )
+    }
+
+    pub fn torus_distance(&self, other: &Point, bounds: Bounds) -> f64 {
+        let (dx, dy) = self.torus_displacement(other, bounds);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// The point halfway along the shortest path to `other`.
+    pub fn torus_midpoint(&self, other: &Point, bounds: Bounds) -> Point {
+        let (dx, dy) = self.torus_displacement(other, bounds);
+        self.translate2(dx / 2.0, dy / 2.0, bounds)
+    }
+
+    pub fn torus_direction_to(&self, other: &Point, bounds: Bounds) -> Direction {
+        let (dx, dy) = self.torus_displacement(other, bounds);
+        dy.atan2(dx)
+    }
+
+    pub fn translate(&mut self, dx: f64, dy: f64, bounds: Bounds) {
         self.x += dx;
         self.y += dy;
-        self.wrap();
+        self.wrap(bounds);
     }
 
-    pub fn translate2(&self, dx: f64, dy: f64) -> Point {
+    pub fn translate2(&self, dx: f64, dy: f64, bounds: Bounds) -> Point {
         let mut point = point!(self.x + dx, self.y + dy);
-        point.wrap();
+        point.wrap(bounds);
         point
     }
 
-    pub fn translate3(&mut self, direction: Direction, distance: f64) {
-        self.translate(direction.cos() * distance, direction.sin() * distance);
+    pub fn translate3(&mut self, direction: Direction, distance: f64, bounds: Bounds) {
+        self.translate(direction.cos() * distance, direction.sin() * distance, bounds);
     }
 
-    pub fn translate4(&self, direction: Direction, distance: f64) -> Point {
-        let mut position = self.clone();
-        position.translate(direction.cos() * distance, direction.sin() * distance);
+    pub fn translate4(&self, direction: Direction, distance: f64, bounds: Bounds) -> Point {
+        let mut position = *self;
+        position.translate(direction.cos() * distance, direction.sin() * distance, bounds);
         position
     }
 
@@ -78,20 +103,18 @@ impl Point {
         dy.atan2(dx)
     }
 
-    fn wrap(&mut self) {
-        unsafe {
-            self.x += WIDTH;
-            self.y += HEIGHT;
-            self.x %= WIDTH;
-            self.y %= HEIGHT;
-        }
+    pub fn wrap(&mut self, bounds: Bounds) {
+        self.x += bounds.width;
+        self.y += bounds.height;
+        self.x %= bounds.width;
+        self.y %= bounds.height;
     }
 
     pub fn from_polar(r: f64, theta: f64) -> Point {
         point!(r * theta.cos(), r * theta.sin())
     }
 
-    pub fn to_polar(&self) -> (f64, f64) {
+    pub fn to_polar(self) -> (f64, f64) {
         (self.distance(&point!(0.0, 0.0)), self.y.atan2(self.x))
     }
 }
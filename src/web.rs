@@ -1,25 +1,25 @@
 use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageBitmap};
 
-use super::config::get_config as get_config_;
-use super::config::set_config;
+use super::config::Config;
+use super::render::canvas::{Assets, CanvasRenderer};
+use super::stats::Stats;
 use super::utils::Point;
 use super::world::World;
-use super::{HEIGHT, WIDTH};
 
 use lazy_static::lazy_static;
 use std::sync::Mutex;
 // use web_sys::console::log_1;
 
-const START_TICKS: u32 = 0;
+/// How many events are kept for `poll_events` if the page stops polling.
+const EVENT_BUFFER: usize = 10_000;
 
-// When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
-// allocator.
-#[cfg(feature = "wee_alloc")]
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
-
-static mut WORLD: Mutex<World> = Mutex::new(World::new());
+lazy_static! {
+    static ref WORLD: Mutex<World> = Mutex::new(World::new());
+    static ref SEED: Mutex<Option<u64>> = Mutex::new(None);
+    static ref ASSETS: Mutex<Assets> = Mutex::new(Assets::default());
+    static ref STATS: Mutex<Stats> = Mutex::new(Stats::default());
+}
 
 #[wasm_bindgen]
 pub fn initialise(width: f64, height: f64) {
@@ -30,122 +30,184 @@ pub fn initialise(width: f64, height: f64) {
 
 #[wasm_bindgen]
 pub fn reinitialise(mut width: f64, mut height: f64) {
-    width /= get_config_().resolution;
-    height /= get_config_().resolution;
-    set_config("width", format!("{:?}", width).as_str());
-    set_config("height", format!("{:?}", height).as_str());
-    set_config("max_creatures", format!("{:?}", (height*width/800.0) as usize).as_str());
-    set_config("starting_pop", format!("{:?}", (height*width/1200.0) as usize).as_str());
-    unsafe {
-        WIDTH = get_config_().width;
-        HEIGHT = get_config_().height;
-        // log_1(&JsValue::from(format!("{} {}", WIDTH, HEIGHT).as_str()));
-        let mut world = WORLD.lock().unwrap();
-        *world = World::new();
-        for _ in 0..(WIDTH * HEIGHT / 5000.0) as usize {
-            world.debris_locs.push(Point::new_random());
-        }
-        world.spawn_random_organisms(get_config_().starting_pop);
-        for _ in 0..get_config_().lifespan * 3 {
-            world.tick();
-        }
+    let mut world = WORLD.lock().unwrap();
+    let mut config = world.config;
+    width /= config.resolution;
+    height /= config.resolution;
+    config.width = width;
+    config.height = height;
+    config.max_creatures = (height * width / 800.0) as usize;
+    config.starting_pop = (height * width / 1200.0) as usize;
+    // log_1(&JsValue::from(format!("{} {}", width, height).as_str()));
+    *world = match *SEED.lock().unwrap() {
+        Some(seed) => World::with_seed(seed),
+        None => World::new(),
+    };
+    world.config = config;
+    world.spawn_random_debris((width * height / 5000.0) as usize);
+    world.spawn_random_organisms(config.starting_pop);
+    for _ in 0..config.lifespan * 3 {
+        world.tick();
     }
+    world.buffer_events(EVENT_BUFFER);
+    let mut stats = STATS.lock().unwrap();
+    stats.clear();
+    stats.record(&world);
+}
+
+/// Sets the seed used by the next (re)initialisation; `None` seeds from entropy.
+#[wasm_bindgen]
+pub fn set_seed(seed: Option<u64>) {
+    *SEED.lock().unwrap() = seed;
 }
 
 #[wasm_bindgen]
 pub fn render(canvas: HtmlCanvasElement, context: CanvasRenderingContext2d) {
     context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
-    context.set_fill_style(&JsValue::from_str("blue"));
+    context.set_fill_style_str("blue");
     context.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
-    unsafe {
-        let mut world = match WORLD.try_lock() {
-            Ok(world) => world,
-            Err(_) => return,
-        };
-        world.render(&context);
-    }
+    let mut world = match WORLD.try_lock() {
+        Ok(world) => world,
+        Err(_) => return,
+    };
+    let assets = ASSETS.lock().unwrap();
+    let resolution = world.config.resolution;
+    world.render(&mut CanvasRenderer::new(&context, &assets, resolution));
 }
 
 #[wasm_bindgen]
 pub fn render_selected(canvas: HtmlCanvasElement, context: CanvasRenderingContext2d) {
-    unsafe {
-        let mut world = match WORLD.try_lock() {
-            Ok(world) => world,
-            Err(_) => return,
-        };
-        context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
-        world.render_selected(&context);
-    }
+    let mut world = match WORLD.try_lock() {
+        Ok(world) => world,
+        Err(_) => return,
+    };
+    context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+    let assets = ASSETS.lock().unwrap();
+    let resolution = world.config.resolution;
+    world.render_selected(&mut CanvasRenderer::new(&context, &assets, resolution));
 }
 
 #[wasm_bindgen]
 pub fn tick() {
-    unsafe {
-        WORLD.lock().unwrap().tick();
-    }
+    let mut world = WORLD.lock().unwrap();
+    world.tick();
+    STATS.lock().unwrap().record(&world);
 }
 
+/// Births, mutations, deaths and corpse removals since the last call, as a
+/// JSON array.
 #[wasm_bindgen]
-pub fn update_config(key: &str, value: &str) {
-    set_config(key, value)
+pub fn poll_events() -> String {
+    serde_json::to_string(&WORLD.lock().unwrap().drain_events()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn get_stats_csv() -> String {
+    STATS.lock().unwrap().to_csv()
+}
+
+#[wasm_bindgen]
+pub fn get_stats_json() -> String {
+    STATS.lock().unwrap().to_json()
+}
+
+/// Throws a descriptive error to JavaScript if the key or value is rejected.
+#[wasm_bindgen]
+pub fn update_config(key: &str, value: &str) -> Result<(), JsValue> {
+    WORLD
+        .lock()
+        .unwrap()
+        .config
+        .set(key, value)
+        .map_err(|err| JsValue::from_str(&err.to_string()))
 }
 
 #[wasm_bindgen]
 pub fn get_config() -> String {
-    get_config_().as_json()
+    WORLD.lock().unwrap().config_as_json()
+}
+
+/// Applies a JSON preset exported by `get_config`. The world keeps its
+/// current size, which follows the browser window.
+#[wasm_bindgen]
+pub fn import_config(json: &str) -> Result<(), JsValue> {
+    let mut config = Config::from_json(json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let mut world = WORLD.lock().unwrap();
+    config.width = world.config.width;
+    config.height = world.config.height;
+    world.config = config;
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn save_world() -> String {
+    WORLD.lock().unwrap().save()
+}
+
+/// Replaces the running world with a snapshot from `save_world`.
+#[wasm_bindgen]
+pub fn load_world(json: &str) -> Result<(), JsValue> {
+    let mut loaded = World::load(json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    loaded.buffer_events(EVENT_BUFFER);
+    let mut stats = STATS.lock().unwrap();
+    stats.clear();
+    stats.record(&loaded);
+    *WORLD.lock().unwrap() = loaded;
+    Ok(())
 }
 
 #[wasm_bindgen]
 pub fn get_world_data() -> String {
-    unsafe { WORLD.lock().unwrap().info_as_json() }
+    WORLD.lock().unwrap().info_as_json()
+}
+
+/// The current world as a standalone SVG document.
+#[wasm_bindgen]
+pub fn get_world_svg(legend: bool) -> String {
+    WORLD.lock().unwrap().to_svg(legend)
+}
+
+/// The coefficient of relatedness between two creatures, living or not.
+#[wasm_bindgen]
+pub fn get_relatedness(a: u32, b: u32) -> f64 {
+    WORLD.lock().unwrap().pedigree().relatedness(a, b)
+}
+
+/// Every creature of the run as a GraphML pedigree.
+#[wasm_bindgen]
+pub fn get_pedigree_graphml() -> String {
+    let world = WORLD.lock().unwrap();
+    world.pedigree().to_graphml(&world.config)
+}
+
+/// The first-parent lineages of the run as Newick trees.
+#[wasm_bindgen]
+pub fn get_pedigree_newick() -> String {
+    WORLD.lock().unwrap().pedigree().to_newick()
 }
 
 #[wasm_bindgen]
 pub fn on_click(x: f64, y: f64) -> Option<String> {
-    let mut closest = 0;
-    let mut distance = 1000.0;
-    unsafe {
-        let mut world = WORLD.lock().unwrap();
-        for i in 0..world.creatures.len() {
-            let creature = &mut world.creatures[i];
-            let dx = creature.position.x - x / get_config_().resolution;
-            let dy = creature.position.y - y / get_config_().resolution;
-            let d = dx * dx + dy * dy;
-            if d < distance {
-                distance = d;
-                closest = i;
-            }
-        }
-        if distance < 100.0 {
-            world.selected = Some(world.creatures[closest].id);
-            Some(world.creatures[closest].get_info_as_json())
-        } else {
-            world.selected = None;
-            None
-        }
-    }
+    let mut world = WORLD.lock().unwrap();
+    let res = world.config.resolution;
+    let picked = world
+        .creature_near(Point::new(x / res, y / res), 10.0)
+        .map(|creature| (creature.id, creature.get_info_as_json(&world.config)));
+    world.selected = picked.as_ref().map(|(id, _)| *id);
+    picked.map(|(_, info)| info)
 }
 
 #[wasm_bindgen]
 pub fn load_fish(fish: ImageBitmap) {
-    unsafe {
-        let mut world = WORLD.lock().unwrap();
-        world.fish = Some(fish);
-    };
+    ASSETS.lock().unwrap().fish = Some(fish);
 }
 
 #[wasm_bindgen]
 pub fn load_bg(bg: ImageBitmap) {
-    unsafe {
-        let mut world = WORLD.lock().unwrap();
-        world.bg = Some(bg);
-    };
+    ASSETS.lock().unwrap().bg = Some(bg);
 }
 
 #[wasm_bindgen]
 pub fn load_debris(debris: ImageBitmap) {
-    unsafe {
-        let mut world = WORLD.lock().unwrap();
-        world.debris = Some(debris);
-    };
+    ASSETS.lock().unwrap().debris = Some(debris);
 }
@@ -1,224 +1,316 @@
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::render::{draw_debris, draw_fish, render_bg};
+use crate::config::Config;
+use crate::events::{DeathCause, Event, EventBus};
+use crate::grid::SpatialGrid;
+use crate::pedigree::Pedigree;
 use crate::utils::Point;
-use crate::{config::get_config, render::draw_outline};
 
 use super::creature::Creature;
-use super::gene::ColourGene;
-use web_sys::{CanvasRenderingContext2d, ImageBitmap};
+use super::gene::Skin;
 
+/// Grid cells are never smaller than this, however short the mate search
+/// radius, so the grid stays a reasonable size.
+const MIN_CELL_SIZE: f64 = 10.0;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct World {
+    pub config: Config,
     pub creatures: Vec<Creature>,
-    pub fish: Option<ImageBitmap>,
-    pub bg: Option<ImageBitmap>,
-    pub debris: Option<ImageBitmap>,
     pub debris_locs: Vec<Point>,
     pub frame_counter: u8,
     pub selected: Option<u32>,
+    #[allow(dead_code)]
     direction: f64,
     focus: Point,
+    next_id: u32,
+    ticks: u64,
+    births: u64,
+    deaths: u64,
+    pedigree: Pedigree,
+    rng: ChaCha8Rng,
+    #[serde(skip)]
+    grid: SpatialGrid,
+    #[serde(skip)]
+    pub(crate) events: EventBus,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World::new()
+    }
 }
 
 impl World {
-    pub const fn new() -> World {
+    /// Creates a world seeded from the OS entropy source.
+    pub fn new() -> World {
+        World::from_rng(ChaCha8Rng::from_entropy())
+    }
+
+    /// Creates a world whose every random draw is derived from `seed`, so the
+    /// same seed and config always produce the same population on the same
+    /// target. Native and wasm builds use different libm implementations of
+    /// the trigonometry creatures swim with, so their runs aren't guaranteed
+    /// to be bit-identical.
+    pub fn with_seed(seed: u64) -> World {
+        World::from_rng(ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: ChaCha8Rng) -> World {
         World {
+            config: Config::new(),
             creatures: Vec::new(),
-            fish: None,
-            bg: None,
-            debris: None,
             debris_locs: Vec::new(),
             frame_counter: 0,
             selected: None,
             direction: 0.0,
             focus: Point { x: 0.0, y: 0.0 },
+            next_id: 0,
+            ticks: 0,
+            births: 0,
+            deaths: 0,
+            pedigree: Pedigree::default(),
+            rng,
+            grid: SpatialGrid::default(),
+            events: EventBus::default(),
         }
     }
 
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// How many times `tick` has run since the world was created.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// How many creatures have been born to parents since the world was
+    /// created, not counting spawned ones.
+    pub fn births(&self) -> u64 {
+        self.births
+    }
+
+    /// How many creatures have died of old age since the world was created.
+    pub fn deaths(&self) -> u64 {
+        self.deaths
+    }
+
+    /// Every living creature and as many dead ones as
+    /// `Config::pedigree_capacity` allows, including those since removed.
+    pub fn pedigree(&self) -> &Pedigree {
+        &self.pedigree
+    }
+
     pub fn spawn_random_organisms(&mut self, count: usize) {
         for _ in 0..count {
-            self.creatures.push(Creature::new_random());
+            let id = self.next_id();
+            let creature = Creature::new_random(id, &self.config, &mut self.rng);
+            self.pedigree.insert(&creature, self.ticks);
+            self.creatures.push(creature);
+        }
+        self.rebuild_grid();
+    }
+
+    pub fn spawn_random_debris(&mut self, count: usize) {
+        for _ in 0..count {
+            let loc = Point::new_random(&mut self.rng, self.config.bounds());
+            self.debris_locs.push(loc);
         }
     }
 
     pub fn tick(&mut self) {
-        if rand::random::<u8>() < 8 {
-            self.focus = Point::new_random();
+        self.ticks += 1;
+        let tick = self.ticks;
+        if self.rng.gen::<u8>() < 8 {
+            self.focus = Point::new_random(&mut self.rng, self.config.bounds());
         }
         for creature in &mut self.creatures {
-            let direction = creature.position.direction_to(&self.focus);
-            creature.tick(direction);
+            let was_alive = creature.is_alive;
+            creature.tick(self.focus, &self.config, &mut self.rng);
+            if was_alive && !creature.is_alive {
+                self.deaths += 1;
+                self.pedigree.record_death(creature.id, tick);
+                self.events.emit(Event::Death {
+                    tick,
+                    id: creature.id,
+                    cause: DeathCause::OldAge,
+                    age: creature.age,
+                    offspring: creature.offspring.len(),
+                });
+            }
         }
-        self.creatures
-            .retain(|creature| creature.age < get_config().lifespan + 15);
-        if self.creatures.len() < get_config().max_creatures {
+        let lifespan = self.config.lifespan + self.config.corpse_persistence;
+        let events = &mut self.events;
+        self.creatures.retain(|creature| {
+            let keep = creature.age <= lifespan;
+            if !keep {
+                events.emit(Event::CorpseRemoved {
+                    tick,
+                    id: creature.id,
+                });
+            }
+            keep
+        });
+        if self.creatures.len() < self.config.max_creatures {
             self.reproduce();
         }
+        self.pedigree.prune(self.config.pedigree_capacity);
+        self.rebuild_grid();
     }
 
-    fn reproduce(&mut self) {
+    /// Pairs up eligible creatures with their nearest available mate and adds
+    /// their offspring. Normally run by `tick` while below `max_creatures`.
+    pub fn reproduce(&mut self) {
+        let config = self.config;
+        let bounds = config.bounds();
         let mut new_creatures = Vec::new();
-        self.creatures.shuffle(&mut rand::thread_rng());
+        self.creatures.shuffle(&mut self.rng);
+        self.rebuild_grid();
         for i in 0..self.creatures.len() {
-            if new_creatures.len() >= self.creatures.len() / 20 {
+            if new_creatures.len() >= self.creatures.len() / config.birth_rate_divisor {
                 break;
             }
             {
                 let creature = &self.creatures[i];
-                if creature.last_reproduced < 30 || !creature.is_alive || creature.age < 50 {
+                if creature.last_reproduced < config.reproduction_cooldown
+                    || !creature.is_alive
+                    || creature.age < config.breeding_age
+                {
                     continue;
                 }
             }
-            let mut closest_distance = 1000.0;
+            let position = self.creatures[i].position;
+            let mut closest_distance = f64::MAX;
             let mut closest = None;
-            for j in i + 1..self.creatures.len() {
-                if i == j {
+            for j in self.grid.query(position, config.mate_search_radius) {
+                if j <= i {
                     continue;
                 };
-                if self.creatures[j].last_reproduced < 15 || !self.creatures[j].is_alive {
+                if self.creatures[j].last_reproduced < config.mate_cooldown
+                    || !self.creatures[j].is_alive
+                {
                     continue;
                 }
-                let distance = self.creatures[i]
-                    .position
-                    .distance(&self.creatures[j].position);
-                if distance < closest_distance {
+                let distance = position.torus_distance(&self.creatures[j].position, bounds);
+                // Ties go to the lowest index, as the grid yields candidates
+                // in no particular order.
+                if distance < closest_distance
+                    || (distance == closest_distance && Some(j) < closest)
+                {
                     closest_distance = distance;
                     closest = Some(j);
                 }
             }
-            if closest_distance > 25.0 {
+            if closest_distance > config.mate_search_radius {
                 continue;
             };
             if let Some(j) = closest {
-                let new_creature = Creature::from_parents([&self.creatures[i], &self.creatures[j]]);
+                let id = self.next_id();
+                let (new_creature, mutations) = Creature::from_parents(
+                    id,
+                    [&self.creatures[i], &self.creatures[j]],
+                    &config,
+                    &mut self.rng,
+                );
                 self.creatures[i].last_reproduced = 0;
                 self.creatures[j].last_reproduced = 0;
                 self.creatures[i].offspring.push(new_creature.id);
                 self.creatures[j].offspring.push(new_creature.id);
+                self.events.emit(Event::Birth {
+                    tick: self.ticks,
+                    id: new_creature.id,
+                    parents: new_creature.parents,
+                    position: new_creature.position,
+                });
+                for (parent, mutation) in mutations {
+                    self.events.emit(Event::Mutation {
+                        tick: self.ticks,
+                        id: new_creature.id,
+                        parent,
+                        mutation,
+                    });
+                }
+                self.pedigree.insert(&new_creature, self.ticks);
                 new_creatures.push(new_creature);
             }
         }
+        self.births += new_creatures.len() as u64;
         self.creatures.append(&mut new_creatures);
     }
 
+    /// Re-indexes creature positions. Called whenever creatures move or the
+    /// order of `creatures` changes, so `grid` always matches it.
+    pub(crate) fn rebuild_grid(&mut self) {
+        let cell_size = self.config.mate_search_radius.max(MIN_CELL_SIZE);
+        self.grid.rebuild(
+            self.config.bounds(),
+            cell_size,
+            self.creatures.iter().map(|creature| creature.position),
+        );
+    }
+
+    /// Returns the creature closest to `point`, if any lies within `radius`.
+    pub fn creature_near(&self, point: Point, radius: f64) -> Option<&Creature> {
+        let bounds = self.config.bounds();
+        let mut closest: Option<(f64, usize)> = None;
+        for i in self.grid.query(point, radius) {
+            let distance = self.creatures[i].position.torus_distance(&point, bounds);
+            if distance < radius && closest.is_none_or(|best| (distance, i) < best) {
+                closest = Some((distance, i));
+            }
+        }
+        closest.map(|(_, i)| &self.creatures[i])
+    }
+
     pub fn config_as_json(&self) -> String {
-        get_config().as_json()
+        self.config.as_json()
     }
 
-    fn counts(&self) -> (u16, u16, u16, u16, u16, u16, u16) {
-        let mut colour_counts = (0, 0, 0, 0, 0, 0, 0);
+    /// How many creatures there are with each skin the current dominance
+    /// model allows, in `Skin::all` order.
+    pub fn colour_counts(&self) -> Vec<(Skin, usize)> {
+        let mut counts: Vec<(Skin, usize)> = Skin::all(self.config.colour_dominance)
+            .into_iter()
+            .map(|skin| (skin, 0))
+            .collect();
         for creature in &self.creatures {
-            match creature.colour {
-                ColourGene::Orange => colour_counts.0 += 1,
-                ColourGene::Red => colour_counts.1 += 1,
-                ColourGene::LBlue => colour_counts.2 += 1,
-                ColourGene::DBlue => colour_counts.3 += 1,
-                ColourGene::Black => colour_counts.4 += 1,
-                ColourGene::Yellow => colour_counts.5 += 1,
-                ColourGene::Purple => colour_counts.6 += 1,
+            let skin = creature.skin(&self.config);
+            if let Some((_, count)) = counts.iter_mut().find(|(other, _)| *other == skin) {
+                *count += 1;
             }
         }
-        colour_counts
+        counts
     }
 
+    /// The population, the number of creatures with each skin as
+    /// `{skin}_skin`, genetics and the selected creature's info.
     pub fn info_as_json(&self) -> String {
-        let (
-            orange_skin,
-            red_skin,
-            light_blue_skin,
-            dark_blue_skin,
-            black_skin,
-            yellow_skin,
-            purple_skin,
-        ) = self.counts();
         let mut json = String::from("{");
-        json.push_str(&format!("\"population\": {},", self.creatures.len()));
-        json.push_str(&format!("\"orange_skin\": {},", orange_skin));
-        json.push_str(&format!("\"red_skin\": {},", red_skin));
-        json.push_str(&format!("\"light_blue_skin\": {},", light_blue_skin));
-        json.push_str(&format!("\"dark_blue_skin\": {},", dark_blue_skin));
-        json.push_str(&format!("\"black_skin\": {},", black_skin));
-        json.push_str(&format!("\"yellow_skin\": {},", yellow_skin));
-        json.push_str(&format!("\"purple_skin\": {}", purple_skin));
+        json.push_str(&format!("\"population\": {}", self.creatures.len()));
+        for (skin, count) in self.colour_counts() {
+            json.push_str(&format!(",\"{}_skin\": {}", skin.name(), count));
+        }
+        json.push_str(&format!(
+            ",\"genetics\": {}",
+            serde_json::to_string(&self.genetics()).unwrap()
+        ));
 
         if let Some(id) = self.selected {
             for creature in &self.creatures {
                 if creature.id == id {
-                    json.push_str(",");
+                    json.push(',');
                     json.push_str("\"selected\": ");
-                    json.push_str(&creature.get_info_as_json());
+                    json.push_str(&creature.get_info_as_json(&self.config));
                 }
             }
         }
 
-        json.push_str("}");
+        json.push('}');
         json
     }
-
-    pub fn render(&mut self, context: &CanvasRenderingContext2d) {
-        if let Some(bg) = &self.bg {
-            render_bg(context, bg);
-        }
-        if let Some(debris) = &self.debris {
-            for loc in &self.debris_locs {
-                draw_debris(context, &debris, *loc)
-            }
-        }
-        if let Some(image) = &self.fish {
-            let selected_id = self.selected.unwrap_or(0);
-            for creature in &self.creatures {
-                let colour = creature.colour as u8;
-                let frame_counter = if creature.is_alive {
-                    (self.frame_counter / 4) % 8
-                } else {
-                    0
-                };
-                if creature.id == selected_id {
-                    draw_outline(context, "red", creature.position)
-                } else if creature.parents.contains(&selected_id) {
-                    draw_outline(context, "blue", creature.position)
-                } else if creature.offspring.contains(&selected_id) {
-                    draw_outline(context, "green", creature.position)
-                }
-                draw_fish(
-                    context,
-                    colour,
-                    frame_counter,
-                    creature.position,
-                    creature.direction,
-                    image,
-                    creature.age,
-                    false,
-                )
-            }
-        }
-        self.frame_counter = self.frame_counter.wrapping_add(1);
-    }
-
-    pub fn render_selected(&mut self, context: &CanvasRenderingContext2d) {
-        if let (Some(id), Some(image)) = (self.selected, &self.fish) {
-            for creature in &self.creatures {
-                if creature.id != id {
-                    continue;
-                }
-                let colour = creature.colour as u8;
-                let frame_counter = if creature.is_alive {
-                    (self.frame_counter / 4) % 8
-                } else {
-                    0
-                };
-                draw_fish(
-                    context,
-                    colour,
-                    frame_counter,
-                    crate::utils::Point { x: 25.0, y: 25.0 },
-                    creature.direction,
-                    image,
-                    creature.age,
-                    true,
-                )
-            }
-        }
-        self.frame_counter = self.frame_counter.wrapping_add(1);
-    }
 }
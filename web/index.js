@@ -2,7 +2,7 @@ const canvas = document.getElementById("world");
 const viewer = document.getElementById("viewer");
 let sim_speed = 90;
 
-import init, { initialise, reinitialise, tick, get_config, render, get_world_data, on_click, render_selected, update_config, load_fish, load_bg, load_debris } from './pkg/evolution_simulated.js';
+import init, { initialise, reinitialise, tick, get_config, import_config, save_world, load_world, get_world_svg, get_stats_csv, get_pedigree_graphml, get_pedigree_newick, render, get_world_data, on_click, render_selected, update_config, load_fish, load_bg, load_debris } from './pkg/evolution_simulated.js';
 window.addEventListener('resize', _reinitialise, false);
 
 run();
@@ -19,6 +19,7 @@ async function run() {
     initialise(window.innerWidth, window.innerHeight);
     load_images();
     initialise_inputs();
+    add_listeners();
     renderLoop();
     _tick();
 }
@@ -66,15 +67,23 @@ function onClick(event) {
 }
 
 function _update_config(key) {
+    let input = document.getElementById(key);
+    if (input == null) {
+        console.log(`Error: Couldn't get input for ${key}`);
+        return;
+    }
     try {
-        let input = document.getElementById(key);
         if (key == "food_blocks_repr" || key == "movers_can_leaf") {
             update_config(input.name, `${input.checked}`);
         } else {
             update_config(input.name, input.value);
         }
+        input.setCustomValidity("");
     } catch (err) {
-        console.log(`Error: Couldn't get input for ${key}: ${err}`);
+        console.log(`Error: Couldn't update ${key}: ${err}`);
+        input.setCustomValidity(`${err}`);
+        input.reportValidity();
+        input.value = JSON.parse(get_config())[key];
     }
 }
 
@@ -88,24 +97,142 @@ function initialise_inputs() {
             input.checked = config[key];
             input.defaultValue = config[key];
             input.defaultChecked = config[key];
-            input.addEventListener("change", () => _update_config(input.id));
         } catch (err) {
             console.log(`Error: Couldn't get input for ${key}: ${err}`);
         }
     }
+}
+
+// Called once from run(), as initialise_inputs runs again on every restart
+// and resize.
+function add_listeners() {
+    let config = JSON.parse(get_config());
+    for (let key in config) {
+        let input = document.getElementById(key);
+        if (input != null) {
+            input.addEventListener("change", () => _update_config(input.id));
+        }
+    }
     document.getElementById("reset_sim").addEventListener("click", _reinitialise);
+    document.getElementById("export_config").addEventListener("click", export_config);
+    document.getElementById("import_config").addEventListener("change", _import_config);
+    document.getElementById("save_world").addEventListener("click", _save_world);
+    document.getElementById("load_world").addEventListener("change", _load_world);
+    document.getElementById("save_svg").addEventListener("click", _save_svg);
+    document.getElementById("save_stats").addEventListener("click", _save_stats);
+    document.getElementById("save_graphml").addEventListener("click", _save_graphml);
+    document.getElementById("save_newick").addEventListener("click", _save_newick);
     document.getElementById("sim_speed").addEventListener("change", () => {
         sim_speed = 150 - document.getElementById("sim_speed").value;
     });
 }
 
+function refresh_inputs() {
+    let config = JSON.parse(get_config());
+    for (let key in config) {
+        let input = document.getElementById(key);
+        if (input != null) {
+            input.value = config[key];
+        }
+    }
+}
+
+function download(filename, contents, type) {
+    let link = document.createElement("a");
+    link.href = URL.createObjectURL(new Blob([contents], { type: type }));
+    link.download = filename;
+    link.click();
+    URL.revokeObjectURL(link.href);
+}
+
+function export_config() {
+    download("preset.json", get_config(), "application/json");
+}
+
+function _import_config(event) {
+    let file = event.target.files[0];
+    if (file == null) {
+        return;
+    }
+    file.text().then(text => {
+        try {
+            import_config(text);
+            refresh_inputs();
+        } catch (err) {
+            alert(`Couldn't import ${file.name}: ${err}`);
+        }
+        event.target.value = "";
+    });
+}
+
+function _save_world() {
+    download("world.json", save_world(), "application/json");
+}
+
+function _save_svg() {
+    download("world.svg", get_world_svg(true), "image/svg+xml");
+}
+
+function _save_stats() {
+    download("stats.csv", get_stats_csv(), "text/csv");
+}
+
+function _save_graphml() {
+    download("pedigree.graphml", get_pedigree_graphml(), "application/xml");
+}
+
+function _save_newick() {
+    download("lineages.nwk", get_pedigree_newick(), "text/plain");
+}
+
+function _load_world(event) {
+    let file = event.target.files[0];
+    if (file == null) {
+        return;
+    }
+    file.text().then(text => {
+        try {
+            load_world(text);
+            refresh_inputs();
+        } catch (err) {
+            alert(`Couldn't load ${file.name}: ${err}`);
+        }
+        event.target.value = "";
+    });
+}
+
+// Rebuilds the skin counts whenever the dominance model changes which skins
+// there are, labelling "light_blue_skin" as "Light Blue Skin".
+function update_skin_counts(info) {
+    let container = document.getElementById("skin_counts");
+    let keys = Object.keys(info).filter((key) => key.endsWith("_skin"));
+    if (container.dataset.keys == keys.join()) {
+        return;
+    }
+    container.replaceChildren();
+    for (let key of keys) {
+        let item = document.createElement("div");
+        item.className = "info-item";
+        item.textContent = key
+            .split("_")
+            .map((word) => word[0].toUpperCase() + word.slice(1))
+            .join(" ");
+        let value = document.createElement("div");
+        value.className = "info-value";
+        value.id = key;
+        container.append(item, value);
+    }
+    container.dataset.keys = keys.join();
+}
+
 function update_world_data() {
     let info = JSON.parse(get_world_data());
     if (info["Population"] == "0") {
         _reinitialise();
     }
+    update_skin_counts(info);
     for (let key in info) {
-        if (key == "selected") {
+        if (key == "selected" || key == "genetics") {
             continue;
         }
         document.getElementById(key.toLowerCase()).textContent = add_separators(